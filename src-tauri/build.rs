@@ -1,3 +1,5 @@
 fn main() {
+    // Rebuild when a migration is added so sqlx::migrate! embeds it
+    println!("cargo:rerun-if-changed=migrations");
     tauri_build::build()
 }
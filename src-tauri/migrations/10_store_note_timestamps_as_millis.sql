@@ -0,0 +1,56 @@
+-- no-transaction
+-- Rebuild notes with created_at/updated_at as Unix epoch milliseconds instead of CURRENT_TIMESTAMP text.
+-- Foreign keys are switched off so dropping the old table does not cascade into note_tags,
+-- which is only possible outside a transaction.
+PRAGMA foreign_keys = OFF;
+
+BEGIN;
+
+CREATE TABLE notes_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content TEXT NOT NULL,
+    created_at INTEGER NOT NULL DEFAULT (CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER)),
+    updated_at INTEGER NOT NULL DEFAULT (CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER)),
+    manual_order REAL,
+    remind_at TIMESTAMP,
+    title TEXT NOT NULL DEFAULT '',
+    deleted_at TIMESTAMP,
+    is_pinned BOOLEAN NOT NULL DEFAULT 0,
+    archived BOOLEAN NOT NULL DEFAULT 0
+);
+
+-- Old values are whole seconds in UTC; rows that somehow lack one fall back to now
+INSERT INTO notes_new (id, content, created_at, updated_at, manual_order, remind_at, title, deleted_at, is_pinned, archived)
+SELECT
+    id,
+    content,
+    COALESCE(CAST(strftime('%s', created_at) AS INTEGER) * 1000, CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER)),
+    COALESCE(CAST(strftime('%s', updated_at) AS INTEGER) * 1000, CAST(strftime('%s', created_at) AS INTEGER) * 1000, CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER)),
+    manual_order,
+    remind_at,
+    title,
+    deleted_at,
+    is_pinned,
+    archived
+FROM notes;
+
+DROP TABLE notes;
+ALTER TABLE notes_new RENAME TO notes;
+
+-- The full-text triggers were dropped along with the old table; rowids are unchanged so the index stays valid
+CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes BEGIN
+    INSERT INTO notes_fts (rowid, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes BEGIN
+    INSERT INTO notes_fts (notes_fts, rowid, content) VALUES ('delete', old.id, old.content);
+END;
+
+CREATE TRIGGER notes_fts_update AFTER UPDATE OF content ON notes BEGIN
+    INSERT INTO notes_fts (notes_fts, rowid, content) VALUES ('delete', old.id, old.content);
+    INSERT INTO notes_fts (rowid, content) VALUES (new.id, new.content);
+END;
+
+COMMIT;
+
+PRAGMA foreign_keys = ON;
@@ -0,0 +1,2 @@
+-- Optional display color per tag, stored as lowercase "#rrggbb"
+ALTER TABLE tags ADD COLUMN color TEXT;
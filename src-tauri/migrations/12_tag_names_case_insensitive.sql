@@ -0,0 +1,17 @@
+-- Tag names are unique regardless of ASCII case; the oldest tag's spelling wins.
+-- Fold existing case-variants into the oldest tag of each group first.
+INSERT OR IGNORE INTO note_tags (note_id, tag_id)
+SELECT nt.note_id, (SELECT MIN(k.id) FROM tags k WHERE k.name = t.name COLLATE NOCASE)
+FROM note_tags nt
+INNER JOIN tags t ON t.id = nt.tag_id;
+
+DELETE FROM note_tags
+WHERE tag_id IN (
+    SELECT t.id FROM tags t
+    WHERE t.id != (SELECT MIN(k.id) FROM tags k WHERE k.name = t.name COLLATE NOCASE)
+);
+
+DELETE FROM tags
+WHERE id != (SELECT MIN(k.id) FROM tags k WHERE k.name = tags.name COLLATE NOCASE);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_name_nocase ON tags (name COLLATE NOCASE);
@@ -0,0 +1,18 @@
+-- Rebuild note_tags so both references cascade, for databases created before the constraints existed.
+-- Links pointing at missing notes or tags are dropped on the way, since foreign keys are now enforced.
+CREATE TABLE note_tags_new (
+    note_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (note_id, tag_id)
+);
+
+INSERT OR IGNORE INTO note_tags_new (note_id, tag_id)
+SELECT note_id, tag_id FROM note_tags
+WHERE note_id IN (SELECT id FROM notes)
+  AND tag_id IN (SELECT id FROM tags);
+
+DROP TABLE note_tags;
+
+ALTER TABLE note_tags_new RENAME TO note_tags;
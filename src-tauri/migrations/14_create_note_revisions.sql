@@ -0,0 +1,12 @@
+-- Earlier versions of a note, saved by update_note before it overwrites the content
+CREATE TABLE note_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    -- Unix epoch milliseconds
+    saved_at INTEGER NOT NULL,
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_note_revisions_note ON note_revisions (note_id, saved_at);
@@ -0,0 +1,2 @@
+-- Favorites are a filter only; unlike pins they don't affect ordering
+ALTER TABLE notes ADD COLUMN is_favorite BOOLEAN NOT NULL DEFAULT 0;
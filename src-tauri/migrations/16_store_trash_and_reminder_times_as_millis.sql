@@ -0,0 +1,9 @@
+-- deleted_at and remind_at held CURRENT_TIMESTAMP-style UTC text; store Unix epoch milliseconds like
+-- created_at/updated_at. A trashed note whose time can't be read stays in the trash, dated now.
+UPDATE notes
+SET deleted_at = COALESCE(CAST(strftime('%s', deleted_at) AS INTEGER) * 1000, CAST(ROUND((julianday('now') - 2440587.5) * 86400000) AS INTEGER))
+WHERE typeof(deleted_at) = 'text';
+
+UPDATE notes
+SET remind_at = CAST(strftime('%s', remind_at) AS INTEGER) * 1000
+WHERE typeof(remind_at) = 'text';
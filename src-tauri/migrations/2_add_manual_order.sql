@@ -0,0 +1,2 @@
+-- Optional fractional sort key for manual ordering within filtered views
+ALTER TABLE notes ADD COLUMN manual_order REAL;
@@ -0,0 +1,2 @@
+-- UTC timestamp ("YYYY-MM-DD HH:MM:SS", same shape as CURRENT_TIMESTAMP) when a note should resurface
+ALTER TABLE notes ADD COLUMN remind_at TIMESTAMP;
@@ -0,0 +1,6 @@
+-- Reusable text snippets expanded by trigger
+CREATE TABLE IF NOT EXISTS snippets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    trigger TEXT NOT NULL UNIQUE,
+    content TEXT NOT NULL
+);
@@ -0,0 +1,22 @@
+-- Explicit note titles; existing notes get their first non-empty line (without heading markers, max 100 chars)
+ALTER TABLE notes ADD COLUMN title TEXT NOT NULL DEFAULT '';
+
+UPDATE notes SET title = substr(
+    rtrim(
+        ltrim(
+            CASE
+                WHEN instr(ltrim(content, ' ' || char(9) || char(10) || char(13)), char(10)) > 0
+                THEN substr(
+                    ltrim(content, ' ' || char(9) || char(10) || char(13)),
+                    1,
+                    instr(ltrim(content, ' ' || char(9) || char(10) || char(13)), char(10)) - 1
+                )
+                ELSE ltrim(content, ' ' || char(9) || char(10) || char(13))
+            END,
+            '# ' || char(9)
+        ),
+        ' ' || char(9) || char(13)
+    ),
+    1,
+    100
+);
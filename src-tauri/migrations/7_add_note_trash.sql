@@ -0,0 +1,2 @@
+-- Soft delete: trashed notes keep their row (and tags) until purged
+ALTER TABLE notes ADD COLUMN deleted_at TIMESTAMP;
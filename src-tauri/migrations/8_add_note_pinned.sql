@@ -0,0 +1,2 @@
+-- Pinned notes sort ahead of everything else in the main list
+ALTER TABLE notes ADD COLUMN is_pinned BOOLEAN NOT NULL DEFAULT 0;
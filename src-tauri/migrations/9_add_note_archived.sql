@@ -0,0 +1,2 @@
+-- Archived notes stay out of the main list without being deleted
+ALTER TABLE notes ADD COLUMN archived BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,325 @@
+// src-tauri/src/api/database.rs
+
+use crate::api::settings::{ensure_writable, read_settings, write_settings};
+use crate::api::workspaces::active_database_path;
+use crate::core::db::{is_plaintext_database, open_database, DbState};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DatabaseStats {
+    pub note_count: i64,
+    pub tag_count: i64,
+    pub file_size: u64,
+    // created_at of the oldest and newest notes in epoch milliseconds, None when there are no notes
+    pub oldest_note: Option<i64>,
+    pub newest_note: Option<i64>,
+}
+
+/// Why the database couldn't be opened at startup, managed only when setup failed
+pub struct DatabaseSetupError(pub String);
+
+// The -wal and -shm files SQLite keeps next to a database in WAL mode
+fn sidecar_paths(db_path: &Path) -> [PathBuf; 2] {
+    ["-wal", "-shm"].map(|suffix| {
+        let mut path = db_path.as_os_str().to_owned();
+        path.push(suffix);
+        PathBuf::from(path)
+    })
+}
+
+fn remove_database_files(db_path: &Path) {
+    let _ = fs::remove_file(db_path);
+    for sidecar in sidecar_paths(db_path) {
+        let _ = fs::remove_file(sidecar);
+    }
+}
+
+// Replace the active database file with `replacement` and reopen the managed pool on it.
+// Commands arriving mid-swap are served from the replacement copy until the final pool is ready.
+async fn install_database_file(db: &DbState, db_path: &Path, replacement: &Path) -> Result<(), String> {
+    let key = db.key();
+    let interim = open_database(replacement, key.as_deref())
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", replacement.display(), e))?;
+    let previous = db.replace(interim.clone());
+    previous.close().await;
+
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(&interim)
+        .await
+        .map_err(|e| format!("Failed to checkpoint database: {}", e))?;
+    for sidecar in sidecar_paths(db_path) {
+        let _ = fs::remove_file(sidecar);
+    }
+    fs::copy(replacement, db_path)
+        .map_err(|e| format!("Failed to replace {}: {}", db_path.display(), e))?;
+
+    let pool = open_database(db_path, key.as_deref())
+        .await
+        .map_err(|e| format!("Failed to reopen database: {}", e))?;
+    db.replace(pool);
+    interim.close().await;
+    remove_database_files(replacement);
+
+    Ok(())
+}
+
+/// Write a consistent copy of the active database to `target_path`, safe while the app is running
+#[command]
+pub async fn backup_database(target_path: String, db: State<'_, DbState>) -> Result<String, String> {
+    let db = db.pool();
+    let target = PathBuf::from(&target_path);
+    if target.exists() {
+        return Err(format!("{} already exists", target.display()));
+    }
+
+    // VACUUM INTO reads through SQLite, so pages still in the WAL are included
+    sqlx::query("VACUUM INTO ?")
+        .bind(target.to_string_lossy().to_string())
+        .execute(&db)
+        .await
+        .map_err(|e| format!("Failed to back up database: {}", e))?;
+
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Replace the active database with the SQLite file at `source_path`
+#[command]
+pub async fn restore_database(app: AppHandle, source_path: String, db: State<'_, DbState>) -> Result<(), String> {
+    ensure_writable(&app)?;
+    let source = PathBuf::from(&source_path);
+    if !source.is_file() {
+        return Err(format!("Backup not found: {}", source.display()));
+    }
+    // Without a passphrase only plain SQLite files can be opened; encrypted ones are checked by opening them
+    let key = db.key();
+    if key.is_none() && !is_plaintext_database(&source) {
+        return Err(format!("{} is not a SQLite database", source.display()));
+    }
+
+    // Work on a copy so the chosen backup is never modified by migrations
+    let db_path = active_database_path(&app)?;
+    let staging = db_path.with_extension("db.restore");
+    remove_database_files(&staging);
+    fs::copy(&source, &staging)
+        .map_err(|e| format!("Failed to copy backup: {}", e))?;
+
+    let checked = async {
+        let pool = open_database(&staging, key.as_deref())
+            .await
+            .map_err(|e| format!("Failed to open backup: {}", e))?;
+        let integrity: String = sqlx::query_scalar("PRAGMA integrity_check")
+            .fetch_one(&pool)
+            .await
+            .map_err(|e| format!("Failed to check backup: {}", e))?;
+        pool.close().await;
+        if integrity != "ok" {
+            return Err(format!("Backup failed its integrity check: {}", integrity));
+        }
+        Ok(())
+    }
+    .await;
+    if let Err(e) = checked {
+        remove_database_files(&staging);
+        return Err(e);
+    }
+
+    install_database_file(&db, &db_path, &staging).await?;
+
+    // The backup is already in place, so a failed emit is only logged
+    if let Err(e) = app.emit("database-restored", ()) {
+        eprintln!("Failed to emit database restore: {}", e);
+    }
+    Ok(())
+}
+
+/// Rebuild the database file to reclaim space left by deleted rows, returning the bytes freed
+#[command]
+pub async fn vacuum_database(app: AppHandle, db: State<'_, DbState>) -> Result<u64, String> {
+    let db = db.pool();
+    let db_path = active_database_path(&app)?;
+
+    // Measure the main file with the WAL folded in, before and after
+    let checkpointed_size = || async {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(&db)
+            .await
+            .map_err(|e| format!("Failed to checkpoint database: {}", e))?;
+        fs::metadata(&db_path)
+            .map(|m| m.len())
+            .map_err(|e| format!("Failed to read database size: {}", e))
+    };
+
+    let before = checkpointed_size().await?;
+    sqlx::query("VACUUM")
+        .execute(&db)
+        .await
+        .map_err(|e| format!("Failed to vacuum database: {}", e))?;
+    sqlx::query("PRAGMA optimize")
+        .execute(&db)
+        .await
+        .map_err(|e| format!("Failed to optimize database: {}", e))?;
+    let after = checkpointed_size().await?;
+
+    Ok(before.saturating_sub(after))
+}
+
+#[command]
+pub async fn get_database_stats(app: AppHandle, db: State<'_, DbState>) -> Result<DatabaseStats, String> {
+    let db = db.pool();
+
+    // Trashed notes are left out, matching what the note list shows
+    let (note_count, oldest_note, newest_note): (i64, Option<i64>, Option<i64>) = sqlx::query_as(
+        "SELECT COUNT(*), MIN(created_at), MAX(created_at) FROM notes WHERE deleted_at IS NULL"
+    )
+    .fetch_one(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let tag_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tags")
+        .fetch_one(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Include the WAL side files, which can hold recent writes not yet checkpointed
+    let db_path = active_database_path(&app)?;
+    let file_size = std::iter::once(db_path.clone())
+        .chain(sidecar_paths(&db_path))
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|m| m.len())
+        .sum();
+
+    Ok(DatabaseStats {
+        note_count,
+        tag_count,
+        file_size,
+        oldest_note,
+        newest_note,
+    })
+}
+
+/// True while an encrypted database is waiting for `unlock_database`
+#[command]
+pub async fn is_database_locked(app: AppHandle) -> Result<bool, String> {
+    Ok(read_settings(&app).database_encrypted && app.try_state::<DbState>().is_none())
+}
+
+/// The startup failure, if the database couldn't be opened. The `database-error` event can fire
+/// before the window listens, so the frontend asks for it on mount as well.
+#[command]
+pub async fn get_database_error(app: AppHandle) -> Result<Option<String>, String> {
+    Ok(app.try_state::<DatabaseSetupError>().map(|error| error.0.clone()))
+}
+
+/// Open the encrypted database left locked at startup
+#[command]
+pub async fn unlock_database(app: AppHandle, passphrase: String) -> Result<(), String> {
+    if app.try_state::<DbState>().is_some() {
+        return Err("Database is already unlocked".to_string());
+    }
+
+    // A wrong passphrase makes SQLCipher report the file as not a database when migrations run
+    let db_path = active_database_path(&app)?;
+    let pool = open_database(&db_path, Some(&passphrase))
+        .await
+        .map_err(|e| format!("Failed to unlock database: {}", e))?;
+    app.manage(DbState::new(pool, Some(passphrase)));
+
+    // The database is open at this point, so a failed emit is only logged
+    if let Err(e) = app.emit("database-unlocked", ()) {
+        eprintln!("Failed to emit database unlock: {}", e);
+    }
+    Ok(())
+}
+
+/// Encrypt the active database with `passphrase`, change its passphrase, or decrypt it when `None`
+#[command]
+pub async fn set_database_passphrase(
+    app: AppHandle,
+    passphrase: Option<String>,
+    db: State<'_, DbState>,
+) -> Result<(), String> {
+    ensure_writable(&app)?;
+    let key = passphrase.filter(|p| !p.is_empty());
+    let db_path = active_database_path(&app)?;
+    let staging = db_path.with_extension("db.rekey");
+    remove_database_files(&staging);
+
+    // sqlcipher_export copies every table into an attached database keyed with the new
+    // passphrase; attaching with an empty key writes it as plain SQLite
+    let pool = db.pool();
+    let exported = async {
+        let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+        // Don't hand a connection with the attachment back to the pool if a step fails
+        conn.close_on_drop();
+        sqlx::query("ATTACH DATABASE ? AS rekeyed KEY ?")
+            .bind(staging.to_string_lossy().to_string())
+            .bind(key.clone().unwrap_or_default())
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to create encrypted copy: {}", e))?;
+        sqlx::query("SELECT sqlcipher_export('rekeyed')")
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to export database: {}", e))?;
+        sqlx::query("DETACH DATABASE rekeyed")
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok::<(), String>(())
+    }
+    .await;
+    if let Err(e) = exported {
+        remove_database_files(&staging);
+        return Err(e);
+    }
+
+    db.set_key(key.clone());
+    install_database_file(&db, &db_path, &staging).await?;
+
+    let mut settings = read_settings(&app);
+    settings.database_encrypted = key.is_some();
+    write_settings(&app, &settings)
+}
+
+/// Keep the default workspace's database at `path` (a `.db` file) from the next start, or back in
+/// app data when `None`. The current database isn't moved: copy it there with `backup_database` first
+/// to keep its notes, otherwise a new empty database is created at the new location.
+#[command]
+pub async fn set_database_location(app: AppHandle, path: Option<String>) -> Result<(), String> {
+    let path = match path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()) {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            if !path.is_absolute() {
+                return Err(format!("Database location must be an absolute path: {}", path.display()));
+            }
+            if path.is_dir() {
+                return Err(format!("Database location must be a file, not a folder: {}", path.display()));
+            }
+            if path.exists() && !path.is_file() {
+                return Err(format!("Database location is not a file: {}", path.display()));
+            }
+
+            let parent = path
+                .parent()
+                .ok_or_else(|| format!("Invalid database location: {}", path.display()))?;
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            // Permission bits don't tell the whole story (ACLs, read-only mounts), so try a real write
+            let probe = parent.join(format!(".lychee-write-test-{}", std::process::id()));
+            fs::File::create(&probe)
+                .map_err(|e| format!("Database folder is not writable: {}: {}", parent.display(), e))?;
+            let _ = fs::remove_file(&probe);
+
+            Some(path.to_string_lossy().to_string())
+        }
+        None => None,
+    };
+
+    let mut settings = read_settings(&app);
+    settings.database_path = path;
+    write_settings(&app, &settings)
+}
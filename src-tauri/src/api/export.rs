@@ -1,15 +1,24 @@
 // src-tauri/src/api/export.rs
 
-use tauri::{command, AppHandle, Manager};
+use crate::api::workspaces::active_database_path;
+use crate::api::notes::{attach_tags, link_tags, VISIBLE_NOTES};
+use crate::api::settings::{ensure_writable, read_settings, Settings};
+use crate::api::tags::normalize_hex_color;
+use crate::api::themes::{read_theme_css, sanitize_filename};
+use crate::core::db::DbState;
+use crate::core::markdown::{render_html, render_html_with_note_links};
+use crate::core::models::{BackupImportCounts, Note, NoteWithTags, Tag};
+use tauri::{command, AppHandle, Manager, State};
 use base64::Engine;
+use serde::{Deserialize, Serialize};
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag as MarkdownTag, TagEnd};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
 
-#[command]
-pub async fn save_export_pdf(
-    app: AppHandle,
-    base64_data: String,
-    file_name: Option<String>,
-) -> Result<String, String> {
-    // Determine exports directory under app data
+// Notes fetched per query when streaming large exports
+const EXPORT_PAGE_SIZE: i64 = 200;
+
+fn get_exports_dir(app: &AppHandle) -> Result<PathBuf, String> {
     let app_data_dir = app
         .path()
         .app_data_dir()
@@ -20,6 +29,55 @@ pub async fn save_export_pdf(
         std::fs::create_dir_all(&exports_dir)
             .map_err(|e| format!("Failed to create exports directory: {}", e))?;
     }
+    Ok(exports_dir)
+}
+
+// Check that a caller-supplied export folder exists and accepts new files
+fn validate_target_dir(dir: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(dir);
+    if !path.is_dir() {
+        return Err(format!("Export folder does not exist: {}", dir));
+    }
+
+    // Permission bits don't tell the whole story (ACLs, read-only mounts), so try a real write
+    let probe = path.join(format!(".lychee-write-test-{}", std::process::id()));
+    std::fs::File::create(&probe)
+        .map_err(|e| format!("Export folder is not writable: {}: {}", dir, e))?;
+    let _ = std::fs::remove_file(&probe);
+
+    Ok(path)
+}
+
+// Reduce a caller-supplied name to a bare "<name>.pdf" that cannot leave the export folder
+fn pdf_file_name(name: &str) -> Result<String, String> {
+    if name.contains('/') || name.contains('\\') || name.contains("..") {
+        return Err(format!("Invalid file name '{}': path separators and '..' are not allowed", name));
+    }
+
+    let stem = match name.rsplit_once('.') {
+        Some((stem, ext)) if ext.eq_ignore_ascii_case("pdf") => stem,
+        _ => name,
+    };
+    let stem = sanitize_filename(stem);
+    if stem.is_empty() {
+        return Err(format!("Invalid file name '{}'", name));
+    }
+
+    Ok(format!("{}.pdf", stem))
+}
+
+#[command]
+pub async fn save_export_pdf(
+    app: AppHandle,
+    base64_data: String,
+    file_name: Option<String>,
+    target_dir: Option<String>,
+) -> Result<String, String> {
+    // A folder picked by the user, or the exports directory under app data
+    let exports_dir = match target_dir {
+        Some(dir) => validate_target_dir(&dir)?,
+        None => get_exports_dir(&app)?,
+    };
 
     // Decode base64 to bytes (support data URI prefix)
     let comma_idx = base64_data.find(',');
@@ -33,11 +91,22 @@ pub async fn save_export_pdf(
         .decode(b64)
         .map_err(|e| format!("Failed to decode base64 PDF data: {}", e))?;
 
+    // Catch empty payloads and HTML error pages before they land on disk as a .pdf
+    if bytes.is_empty() {
+        return Err("PDF data is empty".to_string());
+    }
+    if !bytes.starts_with(b"%PDF-") {
+        return Err("Data is not a PDF document (missing %PDF- header)".to_string());
+    }
+
     // Build filename
-    let name = file_name.unwrap_or_else(|| {
-        let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
-        format!("export_{}.pdf", ts)
-    });
+    let name = match file_name {
+        Some(name) => pdf_file_name(&name)?,
+        None => {
+            let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+            format!("export_{}.pdf", ts)
+        }
+    };
 
     let file_path = exports_dir.join(name);
     std::fs::write(&file_path, bytes)
@@ -46,4 +115,753 @@ pub async fn save_export_pdf(
     Ok(file_path.to_string_lossy().to_string())
 }
 
+#[command]
+pub async fn export_note_gist(
+    id: i64,
+    app: AppHandle,
+    db: State<'_, DbState>,
+) -> Result<String, String> {
+    let db = db.pool();
+    let (content,) = sqlx::query_as::<_, (String,)>("SELECT content FROM notes WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&db)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Note not found: {}", id))?;
+
+    let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let gist_dir = get_exports_dir(&app)?.join(format!("gist_{}_{}", id, ts));
+    std::fs::create_dir_all(&gist_dir)
+        .map_err(|e| format!("Failed to create gist directory: {}", e))?;
+
+    // Pull each fenced block out into its own file and leave a link in the prose
+    let mut readme = String::new();
+    let mut last_end = 0;
+    let mut block: Option<(String, usize)> = None;
+    let mut code = String::new();
+    let mut count = 0;
+
+    for (event, range) in Parser::new(&content).into_offset_iter() {
+        match event {
+            Event::Start(MarkdownTag::CodeBlock(CodeBlockKind::Fenced(lang))) => {
+                block = Some((lang.to_string(), range.start));
+                code.clear();
+            }
+            Event::Text(text) if block.is_some() => code.push_str(&text),
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((lang, start)) = block.take() {
+                    count += 1;
+                    let file_name = format!("snippet-{}.{}", count, code_extension(&lang));
+                    std::fs::write(gist_dir.join(&file_name), &code)
+                        .map_err(|e| format!("Failed to write {}: {}", file_name, e))?;
+
+                    readme.push_str(&content[last_end..start]);
+                    readme.push_str(&format!("[{}]({})\n", file_name, file_name));
+                    last_end = range.end;
+                }
+            }
+            _ => {}
+        }
+    }
+    readme.push_str(&content[last_end..]);
+
+    std::fs::write(gist_dir.join("README.md"), readme)
+        .map_err(|e| format!("Failed to write README.md: {}", e))?;
+
+    Ok(gist_dir.to_string_lossy().to_string())
+}
+
+#[command]
+pub async fn export_note_html(
+    id: i64,
+    app: AppHandle,
+    db: State<'_, DbState>,
+) -> Result<String, String> {
+    let db = db.pool();
+    let settings = read_settings(&app);
+    let (title, content) = sqlx::query_as::<_, (String, String)>("SELECT title, content FROM notes WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&db)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Note not found: {}", id))?;
+
+    let theme_css = active_theme_css(&app, &settings);
+    let page = note_html_document(&title, &content, &settings, &theme_css);
+
+    let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let file_path = get_exports_dir(&app)?.join(format!("{}_{}.html", export_file_stem(id, &title), ts));
+    std::fs::write(&file_path, page)
+        .map_err(|e| format!("Failed to write HTML file: {}", e))?;
+
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+#[command]
+pub async fn export_note_markdown(
+    id: i64,
+    app: AppHandle,
+    db: State<'_, DbState>,
+) -> Result<String, String> {
+    let db = db.pool();
+    let note = sqlx::query_as::<_, Note>(
+        "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes WHERE id = ?"
+    )
+    .bind(id)
+    .fetch_optional(&db)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Note not found: {}", id))?;
+    let note = attach_tags(vec![note], &db)
+        .await?
+        .pop()
+        .ok_or_else(|| format!("Note not found: {}", id))?;
+
+    let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let file_path = get_exports_dir(&app)?.join(format!("{}_{}.md", export_file_stem(note.id, &note.title), ts));
+    std::fs::write(&file_path, note_markdown(&note))
+        .map_err(|e| format!("Failed to write Markdown file: {}", e))?;
+
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+#[command]
+pub async fn export_database_file(app: AppHandle, db: State<'_, DbState>) -> Result<String, String> {
+    let db = db.pool();
+
+    // Fold the WAL into the main file so the copy is self-contained
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(&db)
+        .await
+        .map_err(|e| format!("Failed to checkpoint database: {}", e))?;
+
+    let db_path = active_database_path(&app)?;
+    let stem = db_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("tree");
+    let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let file_path = get_exports_dir(&app)?.join(format!("{}_{}.db", stem, ts));
+
+    std::fs::copy(&db_path, &file_path)
+        .map_err(|e| format!("Failed to copy database file: {}", e))?;
+
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+#[command]
+pub async fn export_all_ndjson(app: AppHandle, db: State<'_, DbState>) -> Result<String, String> {
+    let db = db.pool();
+    let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let file_path = get_exports_dir(&app)?.join(format!("notes_{}.ndjson", ts));
+
+    let file = std::fs::File::create(&file_path)
+        .map_err(|e| format!("Failed to create export file: {}", e))?;
+    let mut writer = BufWriter::new(file);
+
+    // Page by id so memory stays bounded to one page of notes at a time
+    let mut last_id = 0;
+    loop {
+        let notes = sqlx::query_as::<_, Note>(
+            "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes WHERE id > ? AND deleted_at IS NULL ORDER BY id LIMIT ?"
+        )
+        .bind(last_id)
+        .bind(EXPORT_PAGE_SIZE)
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let Some(last) = notes.last() else {
+            break;
+        };
+        last_id = last.id;
+
+        for note in attach_tags(notes, &db).await? {
+            serde_json::to_writer(&mut writer, &note).map_err(|e| e.to_string())?;
+            writer
+                .write_all(b"\n")
+                .map_err(|e| format!("Failed to write export file: {}", e))?;
+        }
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to write export file: {}", e))?;
+    }
+
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+/// Every note that is not in the trash as a Markdown or HTML file, zipped together with a manifest.json
+#[command]
+pub async fn export_all_notes(
+    format: String,
+    app: AppHandle,
+    db: State<'_, DbState>,
+) -> Result<String, String> {
+    let db = db.pool();
+    let settings = read_settings(&app);
+    let extension = match format.as_str() {
+        "markdown" | "md" => "md",
+        "html" => "html",
+        other => return Err(format!("Unsupported export format: {}", other)),
+    };
+    let theme_css = if extension == "html" { active_theme_css(&app, &settings) } else { String::new() };
+
+    let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let file_path = get_exports_dir(&app)?.join(format!("notes_{}.zip", ts));
+    let file = std::fs::File::create(&file_path)
+        .map_err(|e| format!("Failed to create export file: {}", e))?;
+    let mut zip = zip::ZipWriter::new(BufWriter::new(file));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut manifest = Vec::new();
+    let mut last_id = 0;
+    loop {
+        let notes = sqlx::query_as::<_, Note>(
+            "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes
+             WHERE id > ? AND deleted_at IS NULL ORDER BY id LIMIT ?"
+        )
+        .bind(last_id)
+        .bind(EXPORT_PAGE_SIZE)
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let Some(last) = notes.last() else {
+            break;
+        };
+        last_id = last.id;
+
+        for note in attach_tags(notes, &db).await? {
+            // The id suffix keeps notes with the same title from overwriting each other
+            let entry_name = format!("{}-{}.{}", export_file_stem(note.id, &note.title), note.id, extension);
+            let data = if extension == "html" {
+                note_html_document(&note.title, &note.content, &settings, &theme_css)
+            } else {
+                note_markdown(&note)
+            };
+            zip.start_file(entry_name.as_str(), options)
+                .map_err(|e| format!("Failed to add {}: {}", entry_name, e))?;
+            zip.write_all(data.as_bytes())
+                .map_err(|e| format!("Failed to write {}: {}", entry_name, e))?;
+
+            manifest.push(serde_json::json!({
+                "id": note.id,
+                "title": note.title,
+                "tags": note.tags,
+                "file": entry_name,
+            }));
+        }
+    }
+
+    let manifest = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    zip.start_file("manifest.json", options)
+        .map_err(|e| format!("Failed to add manifest.json: {}", e))?;
+    zip.write_all(manifest.as_bytes())
+        .map_err(|e| format!("Failed to write manifest.json: {}", e))?;
+    zip.finish()
+        .map_err(|e| format!("Failed to finish zip file: {}", e))?;
+
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+#[command]
+pub async fn export_static_site(app: AppHandle, db: State<'_, DbState>) -> Result<String, String> {
+    let db = db.pool();
+    let settings = read_settings(&app);
+
+    // Archived notes stay private, like trashed ones
+    let query = format!(
+        "SELECT n.id, n.title, n.content, n.created_at, n.updated_at, n.is_pinned, n.is_favorite FROM notes n
+         WHERE {} ORDER BY n.created_at DESC",
+        VISIBLE_NOTES
+    );
+    let notes = sqlx::query_as::<_, Note>(&query)
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+    let notes = attach_tags(notes, &db).await?;
+
+    // Regenerated from scratch each time so deleted notes don't linger as pages
+    let site_dir = get_exports_dir(&app)?.join("site");
+    if site_dir.exists() {
+        std::fs::remove_dir_all(&site_dir)
+            .map_err(|e| format!("Failed to clear previous site export: {}", e))?;
+    }
+    std::fs::create_dir_all(&site_dir)
+        .map_err(|e| format!("Failed to create site directory: {}", e))?;
 
+    let theme_css = active_theme_css(&app, &settings);
+    std::fs::write(site_dir.join("style.css"), format!("{}\n{}", SITE_BASE_CSS, theme_css))
+        .map_err(|e| format!("Failed to write style.css: {}", e))?;
+
+    let mut index_items = String::new();
+    let mut all_tags = std::collections::BTreeSet::new();
+    // Links to notes left out of the site (archived, trashed or gone) lose their href
+    let exported: std::collections::HashSet<i64> = notes.iter().map(|note| note.id).collect();
+    let note_href = |id: i64| exported.contains(&id).then(|| site_page_name(id));
+
+    for note in &notes {
+        let title = if note.title.is_empty() { "Untitled" } else { note.title.as_str() };
+        let title = escape_html(title);
+        let tags_html = note
+            .tags
+            .iter()
+            .map(|tag| format!("<span class=\"tag\">{}</span>", escape_html(tag)))
+            .collect::<String>();
+        let body = render_html_with_note_links(&note.content, true, &settings.markdown_options, note_href);
+
+        let page = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <link rel=\"stylesheet\" href=\"style.css\">\n</head>\n<body>\n\
+             <nav><a href=\"index.html\">&larr; All notes</a></nav>\n\
+             <article>\n<div class=\"tags\">{tags_html}</div>\n{body}</article>\n</body>\n</html>\n"
+        );
+        let page_name = site_page_name(note.id);
+        std::fs::write(site_dir.join(&page_name), page)
+            .map_err(|e| format!("Failed to write {}: {}", page_name, e))?;
+
+        all_tags.extend(note.tags.iter().cloned());
+        // A JSON array, since tag names may contain any separator
+        let tags_json = serde_json::to_string(&note.tags).map_err(|e| e.to_string())?;
+        index_items.push_str(&format!(
+            "<li data-tags=\"{}\"><a href=\"{}\">{}</a> {}</li>\n",
+            escape_html(&tags_json),
+            page_name,
+            title,
+            tags_html
+        ));
+    }
+
+    let filter_buttons = all_tags
+        .iter()
+        .map(|tag| {
+            let tag = escape_html(tag);
+            format!("<button data-tag=\"{}\">{}</button>", tag, tag)
+        })
+        .collect::<String>();
+
+    let index = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Notes</title>\n\
+         <link rel=\"stylesheet\" href=\"style.css\">\n</head>\n<body>\n\
+         <h1>Notes</h1>\n<div class=\"filters\"><button data-tag=\"\" class=\"active\">All</button>{filter_buttons}</div>\n\
+         <ul class=\"notes\">\n{index_items}</ul>\n<script>{SITE_FILTER_JS}</script>\n</body>\n</html>\n"
+    );
+    std::fs::write(site_dir.join("index.html"), index)
+        .map_err(|e| format!("Failed to write index.html: {}", e))?;
+
+    Ok(site_dir.to_string_lossy().to_string())
+}
+
+#[command]
+pub async fn export_tags_json(app: AppHandle, db: State<'_, DbState>) -> Result<String, String> {
+    let db = db.pool();
+    let tags = sqlx::query_as::<_, Tag>("SELECT id, name, color FROM tags ORDER BY name")
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let data = serde_json::to_string_pretty(&tags).map_err(|e| e.to_string())?;
+    let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let file_path = get_exports_dir(&app)?.join(format!("tags_{}.json", ts));
+    std::fs::write(&file_path, data)
+        .map_err(|e| format!("Failed to write tags file: {}", e))?;
+
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+/// Recreate tags from `export_tags_json`, returning how many were new. Existing names keep their
+/// spelling and pick up the imported color if they had none. Colored tags survive orphan cleanup
+/// without notes; uncolored ones are only kept once a note uses them.
+#[command]
+pub async fn import_tags_json(
+    app: AppHandle,
+    file_path: String,
+    db: State<'_, DbState>,
+) -> Result<u64, String> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let data = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+    let tags: Vec<Tag> = serde_json::from_str(&data)
+        .map_err(|e| format!("Invalid tags file: {}", e))?;
+
+    // Ids from another install mean nothing here; tags are matched by name. Bad colors are dropped, not fatal.
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+    let mut created = 0;
+    for tag in tags {
+        let color = tag.color.as_deref().and_then(|c| normalize_hex_color(c).ok());
+        let inserted = sqlx::query("INSERT OR IGNORE INTO tags (name, color) VALUES (?, ?)")
+            .bind(&tag.name)
+            .bind(&color)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
+        if inserted == 0 && color.is_some() {
+            sqlx::query("UPDATE tags SET color = ? WHERE name = ? COLLATE NOCASE AND color IS NULL")
+                .bind(&color)
+                .bind(&tag.name)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        created += inserted;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(created)
+}
+
+/// Write every note outside the trash, with its tags, plus all tags into one JSON file
+#[command]
+pub async fn export_backup_json(app: AppHandle, db: State<'_, DbState>) -> Result<String, String> {
+    let db = db.pool();
+    let tags = sqlx::query_as::<_, Tag>("SELECT id, name, color FROM tags ORDER BY name")
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+    let notes = sqlx::query_as::<_, Note>(
+        "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes WHERE deleted_at IS NULL ORDER BY id"
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+    let archived: std::collections::HashSet<i64> =
+        sqlx::query_scalar("SELECT id FROM notes WHERE deleted_at IS NULL AND archived = 1")
+            .fetch_all(&db)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect();
+
+    let notes = attach_tags(notes, &db)
+        .await?
+        .into_iter()
+        .map(|note| BackupNote {
+            archived: archived.contains(&note.id),
+            note,
+        })
+        .collect();
+    let backup = Backup {
+        version: BACKUP_VERSION,
+        tags,
+        notes,
+    };
+
+    let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let file_path = get_exports_dir(&app)?.join(format!("backup_{}.json", ts));
+    let file = std::fs::File::create(&file_path)
+        .map_err(|e| format!("Failed to create backup file: {}", e))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &backup)
+        .map_err(|e| format!("Failed to write backup file: {}", e))?;
+
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+/// Restore a file from `export_backup_json`; notes whose id already exists are skipped
+#[command]
+pub async fn import_backup_json(
+    app: AppHandle,
+    path: String,
+    db: State<'_, DbState>,
+) -> Result<BackupImportCounts, String> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let data = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let backup: Backup = serde_json::from_str(&data)
+        .map_err(|e| format!("Invalid backup file: {}", e))?;
+    if backup.version > BACKUP_VERSION {
+        return Err(format!("Backup version {} is newer than this app supports", backup.version));
+    }
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    // Tags are matched by name, like import_tags_json, so colors come along before notes link to them
+    let mut tags_imported = 0;
+    for tag in &backup.tags {
+        let color = tag.color.as_deref().and_then(|c| normalize_hex_color(c).ok());
+        tags_imported += sqlx::query("INSERT OR IGNORE INTO tags (name, color) VALUES (?, ?)")
+            .bind(&tag.name)
+            .bind(&color)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
+    }
+
+    let mut notes_imported = 0;
+    for BackupNote { note, archived } in &backup.notes {
+        let inserted = sqlx::query(
+            "INSERT OR IGNORE INTO notes (id, title, content, created_at, updated_at, is_pinned, is_favorite, archived)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(note.id)
+        .bind(&note.title)
+        .bind(&note.content)
+        .bind(note.created_at)
+        .bind(note.updated_at)
+        .bind(note.is_pinned)
+        .bind(note.is_favorite)
+        .bind(*archived)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+        if inserted == 0 {
+            continue;
+        }
+
+        // The backup's tag list covers every tag its notes use, so this only links
+        link_tags(&mut tx, note.id, &note.tags).await?;
+        notes_imported += 1;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(BackupImportCounts {
+        notes_imported,
+        tags_imported,
+    })
+}
+
+/// Delete the oldest export files beyond `max_files` and/or older than `max_age_days`
+#[command]
+pub async fn prune_exports(
+    app: AppHandle,
+    max_files: Option<usize>,
+    max_age_days: Option<i64>,
+) -> Result<usize, String> {
+    let exports_dir = get_exports_dir(&app)?;
+
+    // Only top-level files of the kinds the export commands write; folders are left alone
+    let mut files = Vec::new();
+    let entries = std::fs::read_dir(&exports_dir)
+        .map_err(|e| format!("Failed to read exports directory: {}", e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        let known = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .is_some_and(|ext| EXPORT_EXTENSIONS.contains(&ext));
+        if !path.is_file() || !known {
+            continue;
+        }
+        let modified = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        files.push((modified, path));
+    }
+
+    // Newest first, so everything past max_files is the oldest
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+
+    // An age reaching back past what SystemTime can hold keeps every file
+    let cutoff = max_age_days.and_then(|days| {
+        let secs = (days.max(0) as u64).checked_mul(24 * 60 * 60)?;
+        std::time::SystemTime::now().checked_sub(std::time::Duration::from_secs(secs))
+    });
+
+    let mut removed = 0;
+    for (index, (modified, path)) in files.iter().enumerate() {
+        let over_count = max_files.is_some_and(|max| index >= max);
+        let too_old = cutoff.is_some_and(|cutoff| *modified < cutoff);
+        if over_count || too_old {
+            std::fs::remove_file(path)
+                .map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+// Bumped whenever the backup layout changes incompatibly
+const BACKUP_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Backup {
+    version: u32,
+    tags: Vec<Tag>,
+    notes: Vec<BackupNote>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BackupNote {
+    #[serde(flatten)]
+    note: NoteWithTags,
+    // Missing in backups written before archiving was kept
+    #[serde(default)]
+    archived: bool,
+}
+
+// File types written into the exports directory
+const EXPORT_EXTENSIONS: &[&str] = &["pdf", "ndjson", "json", "db", "html", "md", "zip"];
+
+const SITE_BASE_CSS: &str = r#"body {
+    font-family: system-ui, sans-serif;
+    max-width: 48rem;
+    margin: 2rem auto;
+    padding: 0 1rem;
+    background: var(--color-background-primary, #fff);
+    color: var(--color-text-primary, #222);
+}
+a { color: var(--color-primary, #0366d6); }
+.tag {
+    display: inline-block;
+    margin-right: 0.25rem;
+    padding: 0 0.4rem;
+    border: 1px solid var(--color-border, #ccc);
+    border-radius: 0.5rem;
+    font-size: 0.8em;
+    color: var(--color-text-secondary, #555);
+}
+.filters button.active { font-weight: bold; }
+pre { overflow-x: auto; background: var(--color-background-secondary, #f5f5f5); padding: 0.5rem; }"#;
+
+const SITE_FILTER_JS: &str = r#"
+document.querySelectorAll('.filters button').forEach(function (button) {
+  button.addEventListener('click', function () {
+    var tag = button.getAttribute('data-tag');
+    document.querySelectorAll('.filters button').forEach(function (b) { b.classList.toggle('active', b === button); });
+    document.querySelectorAll('.notes li').forEach(function (item) {
+      var tags = JSON.parse(item.getAttribute('data-tags'));
+      item.style.display = !tag || tags.indexOf(tag) !== -1 ? '' : 'none';
+    });
+  });
+});
+"#;
+
+// File name of a note's page in the static site, relative to the other pages
+fn site_page_name(id: i64) -> String {
+    format!("note-{}.html", id)
+}
+
+fn active_theme_css(app: &AppHandle, settings: &Settings) -> String {
+    match &settings.active_theme {
+        Some(name) => read_theme_css(app, name).unwrap_or_default(),
+        None => String::new(),
+    }
+}
+
+// Standalone HTML page for one note, with the base and theme styles inlined
+fn note_html_document(title: &str, content: &str, settings: &Settings, theme_css: &str) -> String {
+    let body = render_html(content, true, &settings.markdown_options);
+    let page_title = escape_html(if title.is_empty() { "Untitled" } else { title });
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{page_title}</title>\n\
+         <style>\n{SITE_BASE_CSS}\n{theme_css}\n</style>\n</head>\n<body>\n<article>\n{body}</article>\n</body>\n</html>\n"
+    )
+}
+
+// File name stem for a single-note export: the sanitized title, or the note id when that leaves nothing
+fn export_file_stem(id: i64, title: &str) -> String {
+    let stem = sanitize_filename(title);
+    if stem.is_empty() {
+        format!("note-{}", id)
+    } else {
+        stem
+    }
+}
+
+// Raw note content behind a YAML front matter block with its title, tags and creation time
+fn note_markdown(note: &NoteWithTags) -> String {
+    let tags = note
+        .tags
+        .iter()
+        .map(|tag| yaml_string(tag))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let created = chrono::DateTime::from_timestamp_millis(note.created_at)
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_default();
+
+    format!(
+        "---\ntitle: {}\ntags: [{}]\ncreated: {}\n---\n{}",
+        yaml_string(&note.title),
+        tags,
+        created,
+        note.content
+    )
+}
+
+// Double-quoted YAML scalar, so tags like "a: b" or "#x" survive parsing
+fn yaml_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// Map a fence info string like "rust" or "js,ignore" to a file extension
+fn code_extension(info: &str) -> String {
+    let lang = info
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+
+    let ext = match lang.as_str() {
+        "" => "txt",
+        "rust" => "rs",
+        "python" | "py" => "py",
+        "javascript" | "js" => "js",
+        "typescript" | "ts" => "ts",
+        "tsx" => "tsx",
+        "jsx" => "jsx",
+        "shell" | "bash" | "sh" | "zsh" => "sh",
+        "powershell" | "ps1" => "ps1",
+        "ruby" | "rb" => "rb",
+        "golang" | "go" => "go",
+        "csharp" | "c#" | "cs" => "cs",
+        "cpp" | "c++" => "cpp",
+        "kotlin" | "kt" => "kt",
+        "markdown" | "md" => "md",
+        "yaml" | "yml" => "yml",
+        "text" | "plaintext" => "txt",
+        other if other.chars().all(|c| c.is_ascii_alphanumeric()) => other,
+        _ => "txt",
+    };
+    ext.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pdf_file_name;
+
+    #[test]
+    fn pdf_file_name_rejects_paths() {
+        for name in ["../../x.pdf", "..\\x.pdf", "a/b.pdf", "/abs.pdf"] {
+            assert!(pdf_file_name(name).is_err(), "{} should be rejected", name);
+        }
+    }
+
+    #[test]
+    fn pdf_file_name_rejects_empty_stems() {
+        for name in [".pdf", "..", "***.pdf", "?<>|"] {
+            assert!(pdf_file_name(name).is_err(), "{} should be rejected", name);
+        }
+    }
+
+    #[test]
+    fn pdf_file_name_normalizes_extension() {
+        assert_eq!(pdf_file_name("report.PDF").unwrap(), "report.pdf");
+        assert_eq!(pdf_file_name("name").unwrap(), "name.pdf");
+    }
+
+    #[test]
+    fn pdf_file_name_rejects_any_double_dot() {
+        for name in ["notes..final.pdf", "trailing..", "..hidden.pdf", " .. "] {
+            assert!(pdf_file_name(name).is_err(), "{} should be rejected", name);
+        }
+        assert_eq!(pdf_file_name("v1.2.pdf").unwrap(), "v1-2.pdf");
+    }
+}
@@ -0,0 +1,150 @@
+// src-tauri/src/api/import.rs
+
+use crate::api::notes::{insert_note_with_tags, now_millis};
+use crate::api::settings::ensure_writable;
+use crate::core::db::DbState;
+use encoding_rs::Encoding;
+use scraper::{ElementRef, Html, Selector};
+use tauri::{AppHandle, State};
+
+type Result<T> = std::result::Result<T, String>;
+
+#[tauri::command]
+pub async fn find_encoding_issues(db: State<'_, DbState>) -> Result<Vec<i64>> {
+    let db = db.pool();
+    let notes = sqlx::query_as::<_, (i64, String)>("SELECT id, content FROM notes ORDER BY id")
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(notes
+        .into_iter()
+        .filter(|(_, content)| has_encoding_issues(content))
+        .map(|(id, _)| id)
+        .collect())
+}
+
+#[tauri::command]
+pub async fn reimport_note(
+    id: i64,
+    file_path: String,
+    encoding: String,
+    app: AppHandle,
+    db: State<'_, DbState>,
+) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let encoding = Encoding::for_label(encoding.trim().as_bytes())
+        .ok_or_else(|| format!("Unknown encoding: {}", encoding))?;
+
+    let bytes = std::fs::read(&file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+
+    // Refuse to replace the note with content that is still malformed
+    let (content, _, had_errors) = encoding.decode(&bytes);
+    if had_errors {
+        return Err(format!("{} is not valid {}", file_path, encoding.name()));
+    }
+
+    let res = sqlx::query("UPDATE notes SET content = ?, updated_at = ? WHERE id = ?")
+        .bind(content.as_ref())
+        .bind(now_millis())
+        .bind(id)
+        .execute(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if res.rows_affected() == 0 {
+        return Err(format!("Note not found: {}", id));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn import_bookmarks_html(file_path: String, app: AppHandle, db: State<'_, DbState>) -> Result<usize> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let html = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+
+    // Collect everything up front; scraper's document isn't Send so it can't live across awaits
+    let bookmarks = parse_bookmarks(&html);
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+    for bookmark in &bookmarks {
+        let content = format!("# {}\n\n{}", bookmark.title, bookmark.url);
+        insert_note_with_tags(&mut tx, &content, &bookmark.folders).await?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(bookmarks.len())
+}
+
+struct Bookmark {
+    title: String,
+    url: String,
+    folders: Vec<String>,
+}
+
+// Walk a Netscape bookmark file: each <DT><A> is a bookmark, each <DT><H3> names the <DL> folder after it
+fn parse_bookmarks(html: &str) -> Vec<Bookmark> {
+    let document = Html::parse_document(html);
+    let link_selector = Selector::parse("dt > a[href]").expect("valid selector");
+
+    document
+        .select(&link_selector)
+        .map(|link| {
+            let url = link.value().attr("href").unwrap_or_default().to_string();
+            let text = link.text().collect::<String>();
+            let title = if text.trim().is_empty() { url.clone() } else { text.trim().to_string() };
+
+            // The parser nests each folder's <DL> inside the <DT> holding its <H3>
+            let mut folders: Vec<String> = link
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .filter(|el| el.value().name() == "dt")
+                .filter_map(|dt| {
+                    dt.children()
+                        .filter_map(ElementRef::wrap)
+                        .find(|child| child.value().name() == "h3")
+                })
+                .map(|h3| format_tag_name(&h3.text().collect::<String>()))
+                .filter(|name| !name.is_empty())
+                .collect();
+            folders.reverse();
+
+            Bookmark { title, url, folders }
+        })
+        .collect()
+}
+
+// Same shape the frontend gives tags: words joined by '-', each capitalized
+fn format_tag_name(name: &str) -> String {
+    name.split_whitespace()
+        .flat_map(|word| word.split('-'))
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let lower = word.to_lowercase();
+            let mut chars = lower.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+// Helper to spot replacement characters and UTF-8 that was decoded as Windows-1252/Latin-1
+fn has_encoding_issues(content: &str) -> bool {
+    if content.contains('\u{FFFD}') {
+        return true;
+    }
+
+    // "Ã©", "Â " and friends: a UTF-8 lead byte rendered as Latin-1 followed by a continuation byte
+    let chars: Vec<char> = content.chars().collect();
+    chars.windows(2).any(|pair| {
+        matches!(pair[0], '\u{00C2}' | '\u{00C3}') && ('\u{0080}'..='\u{00BF}').contains(&pair[1])
+    }) || content.contains("â€")
+}
@@ -3,3 +3,10 @@ pub mod tags;
 pub mod themes;
 pub mod export;
 pub mod plugins;
+pub mod workspaces;
+pub mod render;
+pub mod import;
+pub mod settings;
+pub mod snippets;
+pub mod storage;
+pub mod database;
@@ -1,188 +1,723 @@
 // src-tauri/src/api/notes.rs
 
-use crate::core::models::{Note, NoteWithTags, CreateNoteRequest, UpdateNoteRequest};
-use tauri::State;
-use sqlx::{Pool, Sqlite};
+use crate::core::models::{Note, NoteChangeKind, NotesChanged, NoteSaveFailed, NoteRevision, NoteWithTags, CreateNoteRequest, UpdateNoteRequest, SidebarCounts, NoteLength, NotePreview, NoteStats, LengthExtremes, StreakInfo, NoteChunk, SimilarPair, SearchPage, MatchSpan, TextSearchHit, TagMatchMode, NoteSortOrder, NotesPage};
+use crate::core::similarity::{candidate_pairs, jaccard, shingles};
+use crate::core::db::DbState;
+use crate::core::markdown::linked_note_ids;
+use crate::api::settings::ensure_writable;
+use crate::api::tags::{get_all_tags, normalize_tag_name};
+use tauri::{AppHandle, Emitter, Manager, State};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 
 type Result<T> = std::result::Result<T, String>;
 
+// Characters of plain text in a note preview, and how much raw content is read to produce it
+const PREVIEW_CHARS: usize = 200;
+const PREVIEW_SOURCE_CHARS: i64 = 1000;
+
+// Reading speed used for reading_time_minutes
+const WORDS_PER_MINUTE: u64 = 200;
+
+/// Notes that lists and searches show, over `notes n`: not in the trash and not archived
+pub(crate) const VISIBLE_NOTES: &str = "n.deleted_at IS NULL AND n.archived = 0";
+
+// Revisions kept per note; older ones are pruned as new ones are saved
+const MAX_REVISIONS_PER_NOTE: i64 = 50;
+
+// Longest window update_note_debounced will hold a write for
+const MAX_DEBOUNCE_MS: u64 = 10_000;
+
+/// Debounced writes waiting to be committed: the generation of the newest pending update per note
+#[derive(Default)]
+pub struct PendingUpdates {
+    next_generation: AtomicU64,
+    latest: Mutex<HashMap<i64, u64>>,
+}
+
+impl PendingUpdates {
+    // Register a new pending write for the note, superseding any earlier one
+    fn schedule(&self, note_id: i64) -> u64 {
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+        self.latest.lock().expect("pending updates lock poisoned").insert(note_id, generation);
+        generation
+    }
+
+    // Claim the note's pending write if `generation` is still the newest one
+    fn take(&self, note_id: i64, generation: u64) -> bool {
+        let mut latest = self.latest.lock().expect("pending updates lock poisoned");
+        if latest.get(&note_id) == Some(&generation) {
+            latest.remove(&note_id);
+            true
+        } else {
+            false
+        }
+    }
+
+    // Drop the note's pending write, e.g. because a direct update supersedes it
+    fn cancel(&self, note_id: i64) {
+        self.latest.lock().expect("pending updates lock poisoned").remove(&note_id);
+    }
+}
+
 #[tauri::command]
-pub async fn create_note(request: CreateNoteRequest, db: State<'_, Pool<Sqlite>>) -> Result<i64> {
-    let res = sqlx::query("INSERT INTO notes (content) VALUES (?)")
+pub async fn create_note(request: CreateNoteRequest, app: AppHandle, db: State<'_, DbState>) -> Result<i64> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    let title = resolve_title(request.title.as_deref(), &request.content);
+    let res = sqlx::query("INSERT INTO notes (title, content) VALUES (?, ?)")
+        .bind(&title)
         .bind(&request.content)
-        .execute(&*db)
+        .execute(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
     let note_id = res.last_insert_rowid();
 
     // Add tags
-    for tag_name in request.tags {
-        let tag_id = sqlx::query_as::<_, (i64,)>("SELECT id FROM tags WHERE name = ?")
-            .bind(&tag_name)
-            .fetch_optional(&*db)
-            .await
-            .map_err(|e| e.to_string())?;
+    link_tags(&mut tx, note_id, &request.tags).await?;
 
-        let tag_id = if let Some((id,)) = tag_id {
-            id
-        } else {
-            sqlx::query("INSERT INTO tags (name) VALUES (?)")
-                .bind(&tag_name)
-                .execute(&*db)
-                .await
-                .map_err(|e| e.to_string())?
-                .last_insert_rowid()
-        };
+    tx.commit().await.map_err(|e| e.to_string())?;
 
-        sqlx::query("INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)")
-            .bind(note_id)
-            .bind(tag_id)
-            .execute(&*db)
-            .await
-            .map_err(|e| e.to_string())?;
-    }
+    emit_notes_changed(&app, note_id, NoteChangeKind::Created);
 
     Ok(note_id)
 }
 
 #[tauri::command]
-pub async fn get_all_notes(db: State<'_, Pool<Sqlite>>) -> Result<Vec<NoteWithTags>> {
+pub async fn get_all_notes(sort_by: Option<NoteSortOrder>, db: State<'_, DbState>) -> Result<Vec<NoteWithTags>> {
+    let db = db.pool();
+    let notes = sqlx::query_as::<_, Note>(&format!(
+        "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes WHERE deleted_at IS NULL AND archived = 0 ORDER BY is_pinned DESC, {}",
+        sort_clause(sort_by.unwrap_or_default())
+    ))
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_tags(notes, &db).await
+}
+
+#[tauri::command]
+pub async fn get_notes_paginated(limit: i64, offset: i64, db: State<'_, DbState>) -> Result<NotesPage> {
+    let db = db.pool();
+    let notes = sqlx::query_as::<_, Note>(
+        "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes WHERE deleted_at IS NULL AND archived = 0 ORDER BY is_pinned DESC, created_at DESC LIMIT ? OFFSET ?"
+    )
+    .bind(limit.max(0))
+    .bind(offset.max(0))
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let (total,) = sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM notes WHERE deleted_at IS NULL AND archived = 0")
+        .fetch_one(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(NotesPage {
+        notes: attach_tags(notes, &db).await?,
+        total,
+    })
+}
+
+/// Same notes and order as get_notes_paginated, with a short preview in place of the content
+#[tauri::command]
+pub async fn get_note_previews(limit: i64, offset: i64, db: State<'_, DbState>) -> Result<Vec<NotePreview>> {
+    let db = db.pool();
+    // Only the start of each note leaves the database
     let notes = sqlx::query_as::<_, Note>(
-        "SELECT id, content, created_at, updated_at FROM notes ORDER BY created_at DESC"
+        "SELECT id, title, substr(content, 1, ?) AS content, created_at, updated_at, is_pinned, is_favorite FROM notes
+         WHERE deleted_at IS NULL AND archived = 0
+         ORDER BY is_pinned DESC, created_at DESC LIMIT ? OFFSET ?"
     )
-    .fetch_all(&*db)
+    .bind(PREVIEW_SOURCE_CHARS)
+    .bind(limit.max(0))
+    .bind(offset.max(0))
+    .fetch_all(&db)
     .await
     .map_err(|e| e.to_string())?;
 
-    let mut notes_with_tags = Vec::new();
-    for note in notes {
-        let tags = get_tags_for_note_internal(note.id, &db).await?;
-        notes_with_tags.push(NoteWithTags {
+    Ok(attach_tags(notes, &db)
+        .await?
+        .into_iter()
+        .map(|note| NotePreview {
+            preview: markdown_preview(&note.content),
             id: note.id,
-            content: note.content,
+            title: note.title,
             created_at: note.created_at,
             updated_at: note.updated_at,
-            tags,
-        });
-    }
+            is_pinned: note.is_pinned,
+            is_favorite: note.is_favorite,
+            tags: note.tags,
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_note_count(db: State<'_, DbState>) -> Result<i64> {
+    let db = db.pool();
+    // Same notes get_all_notes would return, without loading them
+    let (count,) = sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM notes WHERE deleted_at IS NULL AND archived = 0")
+        .fetch_one(&db)
+        .await
+        .map_err(|e| e.to_string())?;
 
-    Ok(notes_with_tags)
+    Ok(count)
 }
 
 #[tauri::command]
-pub async fn get_note_by_id(id: i64, db: State<'_, Pool<Sqlite>>) -> Result<Option<NoteWithTags>> {
+pub async fn get_note_by_id(id: i64, db: State<'_, DbState>) -> Result<Option<NoteWithTags>> {
+    let db = db.pool();
     let note = sqlx::query_as::<_, Note>(
-        "SELECT id, content, created_at, updated_at FROM notes WHERE id = ?"
+        "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes WHERE id = ?"
     )
     .bind(id)
-    .fetch_optional(&*db)
+    .fetch_optional(&db)
     .await
     .map_err(|e| e.to_string())?;
 
     match note {
-        Some(note) => {
-            let tags = get_tags_for_note_internal(note.id, &db).await?;
-            Ok(Some(NoteWithTags {
-                id: note.id,
-                content: note.content,
-                created_at: note.created_at,
-                updated_at: note.updated_at,
-                tags,
-            }))
-        }
+        Some(note) => Ok(attach_tags(vec![note], &db).await?.pop()),
         None => Ok(None),
     }
 }
 
 #[tauri::command]
-pub async fn update_note(request: UpdateNoteRequest, db: State<'_, Pool<Sqlite>>) -> Result<()> {
-    // Update note content
-    sqlx::query("UPDATE notes SET content = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+pub async fn duplicate_note(id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<i64> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    let (title, content) = sqlx::query_as::<_, (String, String)>("SELECT title, content FROM notes WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Note {} not found", id))?;
+
+    let title = if title.is_empty() { title } else { format!("Copy of {}", title) };
+    let note_id = sqlx::query("INSERT INTO notes (title, content) VALUES (?, ?)")
+        .bind(&title)
+        .bind(&content)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .last_insert_rowid();
+
+    // Link the copy to the original's existing tag rows
+    sqlx::query("INSERT INTO note_tags (note_id, tag_id) SELECT ?, tag_id FROM note_tags WHERE note_id = ?")
+        .bind(note_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(note_id)
+}
+
+#[tauri::command]
+pub async fn update_note(
+    request: UpdateNoteRequest,
+    app: AppHandle,
+    db: State<'_, DbState>,
+    pending: State<'_, PendingUpdates>,
+) -> Result<u64> {
+    ensure_writable(&app)?;
+    // An older debounced write must not land on top of this one
+    pending.cancel(request.id);
+    apply_note_update(&app, &db.pool(), &request).await
+}
+
+/// Like update_note, but held for `debounce_ms`; a newer call for the same note replaces the pending one
+#[tauri::command]
+pub async fn update_note_debounced(
+    request: UpdateNoteRequest,
+    debounce_ms: u64,
+    app: AppHandle,
+    pending: State<'_, PendingUpdates>,
+) -> Result<()> {
+    ensure_writable(&app)?;
+    let generation = pending.schedule(request.id);
+    let delay = Duration::from_millis(debounce_ms.min(MAX_DEBOUNCE_MS));
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        if !app.state::<PendingUpdates>().take(request.id, generation) {
+            return;
+        }
+        // Resolve the pool now, in case the workspace changed while the write was pending
+        let result = match app.try_state::<DbState>() {
+            Some(db) => match ensure_writable(&app) {
+                Ok(()) => apply_note_update(&app, &db.pool(), &request).await,
+                Err(e) => Err(e),
+            },
+            None => Err("Database is locked".to_string()),
+        };
+        // Nobody awaits this task, so tell the windows the edit was lost
+        if let Err(message) = result {
+            eprintln!("Failed to save debounced update for note {}: {}", request.id, message);
+            let payload = NoteSaveFailed { id: request.id, message };
+            if let Err(e) = app.emit("note-save-failed", payload) {
+                eprintln!("Failed to emit note save failure: {}", e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+// Helper function to write a note update with its revision, tags and change event; returns rows updated
+async fn apply_note_update(app: &AppHandle, db: &Pool<Sqlite>, request: &UpdateNoteRequest) -> Result<u64> {
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    // Keep the current text as a revision before it is overwritten
+    save_revision(&mut tx, request.id, &request.content).await?;
+
+    // Update note title and content; its row count tells the caller whether the note exists
+    let title = resolve_title(request.title.as_deref(), &request.content);
+    let updated = sqlx::query("UPDATE notes SET title = ?, content = ?, updated_at = ? WHERE id = ?")
+        .bind(&title)
         .bind(&request.content)
+        .bind(now_millis())
         .bind(request.id)
-        .execute(&*db)
+        .execute(&mut *tx)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+    if updated == 0 {
+        return Ok(0);
+    }
 
     // Remove existing tags
     sqlx::query("DELETE FROM note_tags WHERE note_id = ?")
         .bind(request.id)
-        .execute(&*db)
+        .execute(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
 
     // Add new tags
-    for tag_name in request.tags {
-        let tag_id = sqlx::query_as::<_, (i64,)>("SELECT id FROM tags WHERE name = ?")
-            .bind(&tag_name)
-            .fetch_optional(&*db)
-            .await
-            .map_err(|e| e.to_string())?;
+    link_tags(&mut tx, request.id, &request.tags).await?;
 
-        let tag_id = if let Some((id,)) = tag_id {
-            id
-        } else {
-            sqlx::query("INSERT INTO tags (name) VALUES (?)")
-                .bind(&tag_name)
-                .execute(&*db)
+    // Clean up orphaned tags
+    cleanup_orphaned_tags(&mut tx).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    emit_notes_changed(app, request.id, NoteChangeKind::Updated);
+
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn get_note_revisions(note_id: i64, db: State<'_, DbState>) -> Result<Vec<NoteRevision>> {
+    let db = db.pool();
+    let revisions = sqlx::query_as::<_, NoteRevision>(
+        "SELECT id, note_id, title, content, saved_at FROM note_revisions
+         WHERE note_id = ?
+         ORDER BY saved_at DESC, id DESC"
+    )
+    .bind(note_id)
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(revisions)
+}
+
+/// Revisions of one note whose content contains `query` literally, oldest first
+#[tauri::command]
+pub async fn search_revisions(note_id: i64, query: String, db: State<'_, DbState>) -> Result<Vec<NoteRevision>> {
+    let db = db.pool();
+    let pattern = format!("%{}%", escape_like(&query));
+    let revisions = sqlx::query_as::<_, NoteRevision>(
+        "SELECT id, note_id, title, content, saved_at FROM note_revisions
+         WHERE note_id = ? AND content LIKE ? ESCAPE '\\'
+         ORDER BY saved_at, id"
+    )
+    .bind(note_id)
+    .bind(pattern)
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(revisions)
+}
+
+#[tauri::command]
+pub async fn restore_note_revision(revision_id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<i64> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    let (note_id, title, content) = sqlx::query_as::<_, (i64, String, String)>(
+        "SELECT note_id, title, content FROM note_revisions WHERE id = ?"
+    )
+    .bind(revision_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Revision not found: {}", revision_id))?;
+
+    // The text being replaced becomes a revision too, so a restore can be undone
+    save_revision(&mut tx, note_id, &content).await?;
+
+    let updated = sqlx::query("UPDATE notes SET title = ?, content = ?, updated_at = ? WHERE id = ?")
+        .bind(&title)
+        .bind(&content)
+        .bind(now_millis())
+        .bind(note_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+    if updated == 0 {
+        return Err(format!("Note not found: {}", note_id));
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    emit_notes_changed(&app, note_id, NoteChangeKind::Updated);
+
+    Ok(note_id)
+}
+
+#[tauri::command]
+pub async fn delete_note(id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<u64> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    // Move to the trash; tags stay linked so a restore brings them back
+    let deleted = sqlx::query("UPDATE notes SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL")
+        .bind(now_millis())
+        .bind(id)
+        .execute(&db)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+    if deleted > 0 {
+        emit_notes_changed(&app, id, NoteChangeKind::Deleted);
+    }
+
+    Ok(deleted)
+}
+
+/// Move several notes to the trash in one transaction; returns how many were moved.
+/// Like delete_note, links to tags are kept so a restore brings them back; note_tags rows
+/// and orphaned tags are only cleaned up when a note is purged from the trash.
+#[tauri::command]
+pub async fn delete_notes(ids: Vec<i64>, app: AppHandle, db: State<'_, DbState>) -> Result<u64> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let ids = dedup_ids(ids);
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    // RETURNING reports only the notes actually moved, skipping ids already trashed or missing
+    let deleted_at = now_millis();
+    let mut deleted = Vec::new();
+    for chunk in ids.chunks(500) {
+        let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "UPDATE notes SET deleted_at = ? WHERE deleted_at IS NULL AND id IN ({}) RETURNING id",
+            placeholders
+        );
+        let mut query_builder = sqlx::query_scalar::<_, i64>(&sql).bind(deleted_at);
+        for id in chunk {
+            query_builder = query_builder.bind(id);
+        }
+        deleted.extend(
+            query_builder
+                .fetch_all(&mut *tx)
                 .await
-                .map_err(|e| e.to_string())?
-                .last_insert_rowid()
-        };
+                .map_err(|e| e.to_string())?,
+        );
+    }
 
-        sqlx::query("INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)")
-            .bind(request.id)
-            .bind(tag_id)
-            .execute(&*db)
-            .await
-            .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    for id in &deleted {
+        emit_notes_changed(&app, *id, NoteChangeKind::Deleted);
     }
 
-    // Clean up orphaned tags
-    cleanup_orphaned_tags(&db).await?;
+    Ok(deleted.len() as u64)
+}
+
+#[tauri::command]
+pub async fn restore_note(id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    sqlx::query("UPDATE notes SET deleted_at = NULL WHERE id = ?")
+        .bind(id)
+        .execute(&db)
+        .await
+        .map_err(|e| e.to_string())?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub async fn delete_note(id: i64, db: State<'_, Pool<Sqlite>>) -> Result<()> {
-    sqlx::query("DELETE FROM notes WHERE id = ?")
+pub async fn list_trashed_notes(db: State<'_, DbState>) -> Result<Vec<NoteWithTags>> {
+    let db = db.pool();
+    let notes = sqlx::query_as::<_, Note>(
+        "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC"
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_tags(notes, &db).await
+}
+
+#[tauri::command]
+pub async fn purge_note(id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<u64> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    // note_tags rows go with the note through ON DELETE CASCADE
+    let deleted = sqlx::query("DELETE FROM notes WHERE id = ?")
         .bind(id)
-        .execute(&*db)
+        .execute(&mut *tx)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())?
+        .rows_affected();
 
     // Clean up orphaned tags
-    cleanup_orphaned_tags(&db).await?;
+    cleanup_orphaned_tags(&mut tx).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(deleted)
+}
+
+#[tauri::command]
+pub async fn set_note_pinned(id: i64, pinned: bool, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    sqlx::query("UPDATE notes SET is_pinned = ? WHERE id = ?")
+        .bind(pinned)
+        .bind(id)
+        .execute(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_note_favorite(id: i64, favorite: bool, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    sqlx::query("UPDATE notes SET is_favorite = ? WHERE id = ?")
+        .bind(favorite)
+        .bind(id)
+        .execute(&db)
+        .await
+        .map_err(|e| e.to_string())?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub async fn get_tags_for_note(note_id: i64, db: State<'_, Pool<Sqlite>>) -> Result<Vec<String>> {
+pub async fn get_favorite_notes(db: State<'_, DbState>) -> Result<Vec<NoteWithTags>> {
+    let db = db.pool();
+    // Same visibility and order as the main list, narrowed to favorites
+    let notes = sqlx::query_as::<_, Note>(
+        "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes
+         WHERE is_favorite = 1 AND deleted_at IS NULL AND archived = 0
+         ORDER BY is_pinned DESC, created_at DESC"
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_tags(notes, &db).await
+}
+
+#[tauri::command]
+pub async fn archive_note(id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    set_archived(id, true, &db.pool()).await
+}
+
+#[tauri::command]
+pub async fn unarchive_note(id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    set_archived(id, false, &db.pool()).await
+}
+
+#[tauri::command]
+pub async fn get_archived_notes(db: State<'_, DbState>) -> Result<Vec<NoteWithTags>> {
+    let db = db.pool();
+    let notes = sqlx::query_as::<_, Note>(
+        "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes
+         WHERE archived = 1 AND deleted_at IS NULL
+         ORDER BY updated_at DESC"
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_tags(notes, &db).await
+}
+
+/// Notes that link to no other note and that no other note links to, most recently edited first.
+/// Links are read from the `note:<id>` links in the notes' own content; trashed and archived notes
+/// neither count as links nor show up.
+#[tauri::command]
+pub async fn get_orphan_notes(db: State<'_, DbState>) -> Result<Vec<NoteWithTags>> {
+    let db = db.pool();
+    let query = format!(
+        "SELECT n.id, n.title, n.content, n.created_at, n.updated_at, n.is_pinned, n.is_favorite FROM notes n
+         WHERE {} ORDER BY n.updated_at DESC",
+        VISIBLE_NOTES
+    );
+    let notes = sqlx::query_as::<_, Note>(&query)
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // A link to itself or to a note that isn't listed doesn't tie a note into the graph
+    let ids: HashSet<i64> = notes.iter().map(|note| note.id).collect();
+    let mut linked = HashSet::new();
+    for note in &notes {
+        for target in linked_note_ids(&note.content) {
+            if target != note.id && ids.contains(&target) {
+                linked.insert(note.id);
+                linked.insert(target);
+            }
+        }
+    }
+
+    let orphans = notes.into_iter().filter(|note| !linked.contains(&note.id)).collect();
+    attach_tags(orphans, &db).await
+}
+
+#[tauri::command]
+pub async fn get_tags_for_note(note_id: i64, db: State<'_, DbState>) -> Result<Vec<String>> {
+    let db = db.pool();
     get_tags_for_note_internal(note_id, &db).await
 }
 
 
 
+/// Tag every note in `note_ids` with `tag_name`, creating the tag if needed; returns how many notes gained it
+#[tauri::command]
+pub async fn add_tag_to_notes(note_ids: Vec<i64>, tag_name: String, app: AppHandle, db: State<'_, DbState>) -> Result<u64> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let tag_name = normalize_tag_name(&tag_name);
+    if tag_name.is_empty() {
+        return Err("Tag name must not be empty".to_string());
+    }
+    let note_ids = dedup_ids(note_ids);
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    sqlx::query("INSERT OR IGNORE INTO tags (name) VALUES (?)")
+        .bind(&tag_name)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tag_id = sqlx::query_scalar::<_, i64>("SELECT id FROM tags WHERE name = ? COLLATE NOCASE")
+        .bind(&tag_name)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // OR IGNORE skips notes that already carry the tag, so only new links are counted
+    let mut changed = 0;
+    for chunk in note_ids.chunks(500) {
+        let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "INSERT OR IGNORE INTO note_tags (note_id, tag_id) SELECT id, ? FROM notes WHERE id IN ({})",
+            placeholders
+        );
+        let mut query_builder = sqlx::query(&sql).bind(tag_id);
+        for id in chunk {
+            query_builder = query_builder.bind(id);
+        }
+        changed += query_builder
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
+    }
+
+    // Nothing was linked, so don't leave a freshly created tag behind
+    cleanup_orphaned_tags(&mut tx).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(changed)
+}
+
+/// Remove `tag_name` from every note in `note_ids`; returns how many notes lost it
+#[tauri::command]
+pub async fn remove_tag_from_notes(note_ids: Vec<i64>, tag_name: String, app: AppHandle, db: State<'_, DbState>) -> Result<u64> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let tag_name = normalize_tag_name(&tag_name);
+    let note_ids = dedup_ids(note_ids);
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    let tag_id = sqlx::query_scalar::<_, i64>("SELECT id FROM tags WHERE name = ? COLLATE NOCASE")
+        .bind(&tag_name)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some(tag_id) = tag_id else {
+        return Ok(0);
+    };
+
+    let mut changed = 0;
+    for chunk in note_ids.chunks(500) {
+        let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!("DELETE FROM note_tags WHERE tag_id = ? AND note_id IN ({})", placeholders);
+        let mut query_builder = sqlx::query(&sql).bind(tag_id);
+        for id in chunk {
+            query_builder = query_builder.bind(id);
+        }
+        changed += query_builder
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
+    }
+
+    cleanup_orphaned_tags(&mut tx).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(changed)
+}
+
 #[tauri::command]
-pub async fn search_notes_by_tags(tag_names: Vec<String>, db: State<'_, Pool<Sqlite>>) -> Result<Vec<NoteWithTags>> {
+pub async fn search_notes_by_tags(tag_names: Vec<String>, match_mode: Option<TagMatchMode>, db: State<'_, DbState>) -> Result<Vec<NoteWithTags>> {
     if tag_names.is_empty() {
-        return get_all_notes(db).await;
+        return get_all_notes(None, db).await;
     }
+    let db = db.pool();
+    let match_all = match_mode.unwrap_or_default() == TagMatchMode::All;
 
-    // Build the query to find notes that have ALL the specified tags
+    // Build the query to find notes that have ALL (or ANY of) the specified tags
     let placeholders = tag_names.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let having = if match_all { "HAVING COUNT(DISTINCT t.name) = ?" } else { "" };
     let query = format!(
-        "SELECT DISTINCT n.id, n.content, n.created_at, n.updated_at 
+        "SELECT DISTINCT n.id, n.title, n.content, n.created_at, n.updated_at, n.is_pinned, n.is_favorite 
          FROM notes n 
          INNER JOIN note_tags nt ON n.id = nt.note_id 
          INNER JOIN tags t ON nt.tag_id = t.id 
-         WHERE t.name IN ({}) 
+         WHERE t.name COLLATE NOCASE IN ({}) AND {} 
          GROUP BY n.id 
-         HAVING COUNT(DISTINCT t.name) = ? 
+         {} 
          ORDER BY n.created_at DESC",
-        placeholders
+        placeholders, VISIBLE_NOTES, having
     );
 
     let mut query_builder = sqlx::query_as::<_, Note>(&query);
@@ -193,52 +728,1019 @@ pub async fn search_notes_by_tags(tag_names: Vec<String>, db: State<'_, Pool<Sql
     }
     
     // Bind the count of tags we're looking for
-    query_builder = query_builder.bind(tag_names.len() as i64);
+    if match_all {
+        query_builder = query_builder.bind(tag_names.len() as i64);
+    }
 
     let notes = query_builder
-        .fetch_all(&*db)
+        .fetch_all(&db)
         .await
         .map_err(|e| e.to_string())?;
 
-    let mut notes_with_tags = Vec::new();
-    for note in notes {
-        let tags = get_tags_for_note_internal(note.id, &db).await?;
-        notes_with_tags.push(NoteWithTags {
-            id: note.id,
-            content: note.content,
-            created_at: note.created_at,
-            updated_at: note.updated_at,
-            tags,
-        });
+    attach_tags(notes, &db).await
+}
+
+#[tauri::command]
+pub async fn search_notes_by_text(query: String, include_matches: Option<bool>, db: State<'_, DbState>) -> Result<Vec<TextSearchHit>> {
+    let include_matches = include_matches.unwrap_or(false);
+    let fts_query = fts_match_query(&query);
+    if fts_query.is_empty() {
+        let notes = get_all_notes(None, db).await?;
+        return Ok(notes
+            .into_iter()
+            .map(|note| TextSearchHit {
+                note,
+                matches: include_matches.then(Vec::new),
+            })
+            .collect());
     }
+    let db = db.pool();
 
-    Ok(notes_with_tags)
-}
+    let query = format!(
+        "SELECT n.id, n.title, n.content, n.created_at, n.updated_at, n.is_pinned, n.is_favorite
+         FROM notes_fts
+         INNER JOIN notes n ON n.id = notes_fts.rowid
+         WHERE notes_fts MATCH ? AND {}
+         ORDER BY notes_fts.rank",
+        VISIBLE_NOTES
+    );
+    let notes = sqlx::query_as::<_, Note>(&query)
+        .bind(&fts_query)
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+    let notes = attach_tags(notes, &db).await?;
 
-// Helper function to get tags for a note
-async fn get_tags_for_note_internal(note_id: i64, db: &Pool<Sqlite>) -> Result<Vec<String>> {
-    let tags = sqlx::query_as::<_, (String,)>(
-        "SELECT t.name FROM tags t 
-         INNER JOIN note_tags nt ON t.id = nt.tag_id 
-         WHERE nt.note_id = ? 
-         ORDER BY t.name"
+    if !include_matches {
+        return Ok(notes.into_iter().map(|note| TextSearchHit { note, matches: None }).collect());
+    }
+
+    // FTS5 marks the terms the same MATCH found, so highlights can't drift from the search itself
+    let marked: HashMap<i64, String> = sqlx::query_as::<_, (i64, String)>(
+        "SELECT rowid, highlight(notes_fts, 0, char(1), char(2)) FROM notes_fts WHERE notes_fts MATCH ?"
     )
-    .bind(note_id)
-    .fetch_all(db)
+    .bind(&fts_query)
+    .fetch_all(&db)
     .await
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| e.to_string())?
+    .into_iter()
+    .collect();
 
-    Ok(tags.into_iter().map(|(name,)| name).collect())
+    Ok(notes
+        .into_iter()
+        .map(|note| {
+            let matches = marked.get(&note.id).map(|text| match_spans(text)).unwrap_or_default();
+            TextSearchHit { note, matches: Some(matches) }
+        })
+        .collect())
 }
 
-// Helper function to clean up orphaned tags
-async fn cleanup_orphaned_tags(db: &Pool<Sqlite>) -> Result<()> {
+#[tauri::command]
+pub async fn set_note_order(id: i64, order: Option<f64>, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    sqlx::query("UPDATE notes SET manual_order = ? WHERE id = ?")
+        .bind(order)
+        .bind(id)
+        .execute(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_all_notes_ordered_manual(db: State<'_, DbState>) -> Result<Vec<NoteWithTags>> {
+    let db = db.pool();
+    // Notes without a manual position fall to the end
+    let query = format!(
+        "SELECT n.id, n.title, n.content, n.created_at, n.updated_at, n.is_pinned, n.is_favorite FROM notes n
+         WHERE {}
+         ORDER BY n.manual_order IS NULL, n.manual_order, n.created_at DESC",
+        VISIBLE_NOTES
+    );
+    let notes = sqlx::query_as::<_, Note>(&query)
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    attach_tags(notes, &db).await
+}
+
+#[tauri::command]
+pub async fn renumber_manual_order(app: AppHandle, db: State<'_, DbState>) -> Result<usize> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    // Reset positions to whole numbers, keeping the current relative order
+    let ids = sqlx::query_as::<_, (i64,)>(
+        "SELECT id FROM notes WHERE manual_order IS NOT NULL AND deleted_at IS NULL ORDER BY manual_order, created_at DESC"
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for (position, (id,)) in ids.iter().enumerate() {
+        sqlx::query("UPDATE notes SET manual_order = ? WHERE id = ?")
+            .bind((position + 1) as f64)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(ids.len())
+}
+
+#[tauri::command]
+pub async fn get_sidebar_counts(db: State<'_, DbState>) -> Result<SidebarCounts> {
+    let db = db.pool();
+    let counts = sqlx::query_as::<_, SidebarCounts>(
+        "SELECT
+            (SELECT COUNT(*) FROM notes WHERE deleted_at IS NULL AND archived = 0) AS total,
+            (SELECT COUNT(*) FROM notes WHERE deleted_at IS NULL AND archived = 1) AS archived,
+            (SELECT COUNT(*) FROM notes WHERE deleted_at IS NULL AND archived = 0 AND is_favorite = 1) AS favorites,
+            (SELECT COUNT(*) FROM notes WHERE deleted_at IS NOT NULL) AS trashed,
+            (SELECT COUNT(*) FROM notes n
+             WHERE n.deleted_at IS NULL AND n.archived = 0
+               AND NOT EXISTS (SELECT 1 FROM note_tags nt WHERE nt.note_id = n.id)) AS untagged"
+    )
+    .fetch_one(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(counts)
+}
+
+#[tauri::command]
+pub async fn find_note_by_content(content: String, db: State<'_, DbState>) -> Result<Option<i64>> {
+    let db = db.pool();
+    let target = normalize_content(&content);
+
+    let notes = sqlx::query_as::<_, (i64, String)>("SELECT id, content FROM notes WHERE deleted_at IS NULL ORDER BY id")
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(notes
+        .into_iter()
+        .find(|(_, existing)| normalize_content(existing) == target)
+        .map(|(id, _)| id))
+}
+
+#[tauri::command]
+pub async fn get_note_length_extremes(
+    limit: Option<i64>,
+    include_empty: Option<bool>,
+    db: State<'_, DbState>,
+) -> Result<LengthExtremes> {
+    let db = db.pool();
+    let limit = limit.unwrap_or(5).max(1);
+
+    // LENGTH counts characters for TEXT, and keeps the content itself in the database
+    let longest = sqlx::query_as::<_, NoteLength>(
+        "SELECT id, LENGTH(content) AS length FROM notes WHERE deleted_at IS NULL ORDER BY length DESC, id LIMIT ?"
+    )
+    .bind(limit)
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let empty_filter = if include_empty.unwrap_or(false) {
+        ""
+    } else {
+        "AND LENGTH(TRIM(content)) > 0"
+    };
+    let shortest = sqlx::query_as::<_, NoteLength>(&format!(
+        "SELECT id, LENGTH(content) AS length FROM notes WHERE deleted_at IS NULL {} ORDER BY length ASC, id LIMIT ?",
+        empty_filter
+    ))
+    .bind(limit)
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(LengthExtremes { longest, shortest })
+}
+
+#[tauri::command]
+pub async fn split_note_by_headings(id: i64, archive_original: Option<bool>, app: AppHandle, db: State<'_, DbState>) -> Result<Vec<i64>> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let (content,) = sqlx::query_as::<_, (String,)>("SELECT content FROM notes WHERE id = ? AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(&db)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Note not found: {}", id))?;
+
+    // Offsets of top-level headings; the parser skips lookalikes inside code blocks
+    let heading_starts: Vec<usize> = Parser::new(&content)
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::Heading { level: HeadingLevel::H1, .. }) => Some(range.start),
+            _ => None,
+        })
+        .collect();
+
+    if heading_starts.is_empty() {
+        return Err("Note has no top-level headings to split on".to_string());
+    }
+
+    // Text before the first heading stays with the first section
+    let mut sections = Vec::new();
+    for (i, start) in heading_starts.iter().enumerate() {
+        let section_start = if i == 0 { 0 } else { *start };
+        let section_end = heading_starts.get(i + 1).copied().unwrap_or(content.len());
+        sections.push(content[section_start..section_end].trim().to_string());
+    }
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+    let mut new_ids = Vec::new();
+
+    for section in sections {
+        let new_id = sqlx::query("INSERT INTO notes (title, content) VALUES (?, ?)")
+            .bind(default_title(&section))
+            .bind(&section)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .last_insert_rowid();
+
+        sqlx::query("INSERT INTO note_tags (note_id, tag_id) SELECT ?, tag_id FROM note_tags WHERE note_id = ?")
+            .bind(new_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        new_ids.push(new_id);
+    }
+
+    if archive_original.unwrap_or(false) {
+        sqlx::query("UPDATE notes SET archived = 1 WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(new_ids)
+}
+
+#[tauri::command]
+pub async fn merge_notes(ids: Vec<i64>, separator: String, archive_originals: Option<bool>, app: AppHandle, db: State<'_, DbState>) -> Result<i64> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    // Drop repeats but keep the caller's order, which decides the merged content
+    let ids = dedup_ids_in_order(ids);
+    if ids.len() < 2 {
+        return Err("At least two notes are required to merge".to_string());
+    }
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    // Keep the caller's order rather than the table order
+    let mut contents = Vec::new();
+    for id in &ids {
+        let (content,) = sqlx::query_as::<_, (String,)>("SELECT content FROM notes WHERE id = ? AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Note not found: {}", id))?;
+        contents.push(content);
+    }
+
+    let merged = contents.join(&separator);
+    let new_id = sqlx::query("INSERT INTO notes (title, content) VALUES (?, ?)")
+        .bind(default_title(&merged))
+        .bind(&merged)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .last_insert_rowid();
+
+    let placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let union_query = format!(
+        "INSERT OR IGNORE INTO note_tags (note_id, tag_id)
+         SELECT DISTINCT ?, tag_id FROM note_tags WHERE note_id IN ({})",
+        placeholders
+    );
+    let mut query_builder = sqlx::query(&union_query).bind(new_id);
+    for id in &ids {
+        query_builder = query_builder.bind(id);
+    }
+    query_builder
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if archive_originals.unwrap_or(false) {
+        for id in &ids {
+            sqlx::query("UPDATE notes SET archived = 1 WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(new_id)
+}
+
+/// Consecutive days with at least one new note. Days are UTC calendar dates of the
+/// epoch-millisecond `created_at` values.
+#[tauri::command]
+pub async fn get_writing_streak(db: State<'_, DbState>) -> Result<StreakInfo> {
+    let db = db.pool();
+    let rows = sqlx::query_as::<_, (String,)>(
+        "SELECT DISTINCT DATE(created_at / 1000, 'unixepoch') AS day FROM notes ORDER BY day"
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let days: Vec<chrono::NaiveDate> = rows
+        .iter()
+        .filter_map(|(day,)| chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+        .collect();
+
+    // Longest run anywhere in history
+    let mut longest_streak = 0;
+    let mut run = 0;
+    let mut previous: Option<chrono::NaiveDate> = None;
+    for day in &days {
+        run = match previous {
+            Some(prev) if *day - prev == chrono::Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest_streak = longest_streak.max(run);
+        previous = Some(*day);
+    }
+
+    // The current streak survives until a full day passes without writing
+    let today = chrono::Utc::now().date_naive();
+    let mut current_streak = 0;
+    if let Some(last) = days.last() {
+        if *last == today || *last == today - chrono::Duration::days(1) {
+            let mut expected = *last;
+            for day in days.iter().rev() {
+                if *day != expected {
+                    break;
+                }
+                current_streak += 1;
+                expected -= chrono::Duration::days(1);
+            }
+        }
+    }
+
+    Ok(StreakInfo {
+        current_streak,
+        longest_streak,
+        last_write: days.last().map(|day| day.to_string()),
+    })
+}
+
+#[tauri::command]
+pub async fn get_note_chunk(id: i64, offset: i64, length: i64, db: State<'_, DbState>) -> Result<NoteChunk> {
+    let db = db.pool();
+    if offset < 0 || length < 0 {
+        return Err("Offset and length must not be negative".to_string());
+    }
+
+    // substr() is 1-based and counts characters, so only the window leaves the database
+    let (text, total_len) = sqlx::query_as::<_, (String, i64)>(
+        "SELECT substr(content, ? + 1, ?), LENGTH(content) FROM notes WHERE id = ?"
+    )
+    .bind(offset)
+    .bind(length)
+    .bind(id)
+    .fetch_optional(&db)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Note not found: {}", id))?;
+
+    let end = offset.min(total_len) + text.chars().count() as i64;
+    Ok(NoteChunk {
+        text,
+        total_len,
+        has_more: end < total_len,
+    })
+}
+
+#[tauri::command]
+pub async fn get_note_stats(id: i64, db: State<'_, DbState>) -> Result<NoteStats> {
+    let db = db.pool();
+    let content = sqlx::query_scalar::<_, String>("SELECT content FROM notes WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&db)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Note not found: {}", id))?;
+
+    Ok(note_stats(&content))
+}
+
+#[tauri::command]
+pub async fn set_note_reminder(id: i64, remind_at: String, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let remind_at = parse_reminder_time(&remind_at)?;
+
+    sqlx::query("UPDATE notes SET remind_at = ? WHERE id = ?")
+        .bind(remind_at)
+        .bind(id)
+        .execute(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn clear_note_reminder(id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    sqlx::query("UPDATE notes SET remind_at = NULL WHERE id = ?")
+        .bind(id)
+        .execute(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_due_reminders(db: State<'_, DbState>) -> Result<Vec<NoteWithTags>> {
+    let db = db.pool();
+    let notes = sqlx::query_as::<_, Note>(
+        "SELECT id, title, content, created_at, updated_at, is_pinned, is_favorite FROM notes
+         WHERE remind_at IS NOT NULL AND remind_at <= ? AND deleted_at IS NULL
+         ORDER BY remind_at"
+    )
+    .bind(now_millis())
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_tags(notes, &db).await
+}
+
+#[tauri::command]
+pub async fn find_similar_notes(threshold: f64, db: State<'_, DbState>) -> Result<Vec<SimilarPair>> {
+    let db = db.pool();
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err("Threshold must be between 0 and 1".to_string());
+    }
+
+    let notes = sqlx::query_as::<_, (i64, String)>("SELECT id, content FROM notes WHERE deleted_at IS NULL ORDER BY id")
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let sets: Vec<_> = notes.iter().map(|(_, content)| shingles(content)).collect();
+
+    // Only pairs that collide in a MinHash band are scored exactly
+    let mut pairs: Vec<SimilarPair> = candidate_pairs(&sets)
+        .into_iter()
+        .filter_map(|(i, j)| {
+            let score = jaccard(&sets[i], &sets[j]);
+            (score >= threshold).then(|| SimilarPair {
+                a: notes[i].0,
+                b: notes[j].0,
+                score,
+            })
+        })
+        .collect();
+
+    pairs.sort_by(|x, y| y.score.total_cmp(&x.score).then(x.a.cmp(&y.a)).then(x.b.cmp(&y.b)));
+    Ok(pairs)
+}
+
+#[tauri::command]
+pub async fn create_tag_checklist_note(app: AppHandle, db: State<'_, DbState>) -> Result<i64> {
+    let tags = get_all_tags(db.clone()).await?;
+    let content = tags
+        .iter()
+        .map(|tag| format!("- [ ] {}", tag.name))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let request = CreateNoteRequest {
+        title: Some("Tag Checklist".to_string()),
+        content,
+        tags: Vec::new(),
+    };
+    create_note(request, app, db).await
+}
+
+#[tauri::command]
+pub async fn search_scoped(
+    tag_names: Vec<String>,
+    match_all: bool,
+    text: Option<String>,
+    limit: i64,
+    offset: i64,
+    db: State<'_, DbState>,
+) -> Result<SearchPage> {
+    let db = db.pool();
+
+    // One predicate and bind list shared by the page query and the count query
+    let (predicate, binds) = build_search_predicate(&tag_names, match_all, text.as_deref());
+
+    let page_query = format!(
+        "SELECT n.id, n.title, n.content, n.created_at, n.updated_at, n.is_pinned, n.is_favorite FROM notes n {}
+         ORDER BY n.created_at DESC LIMIT ? OFFSET ?",
+        predicate
+    );
+    let mut page_builder = sqlx::query_as::<_, Note>(&page_query);
+    for bind in &binds {
+        page_builder = match bind {
+            SearchBind::Text(value) => page_builder.bind(value),
+            SearchBind::Int(value) => page_builder.bind(value),
+        };
+    }
+    let notes = page_builder
+        .bind(limit.max(0))
+        .bind(offset.max(0))
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let count_query = format!("SELECT COUNT(*) FROM notes n {}", predicate);
+    let mut count_builder = sqlx::query_as::<_, (i64,)>(&count_query);
+    for bind in &binds {
+        count_builder = match bind {
+            SearchBind::Text(value) => count_builder.bind(value),
+            SearchBind::Int(value) => count_builder.bind(value),
+        };
+    }
+    let (total,) = count_builder
+        .fetch_one(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(SearchPage {
+        results: attach_tags(notes, &db).await?,
+        total,
+    })
+}
+
+#[tauri::command]
+pub async fn search_notes(text: Option<String>, tags: Vec<String>, match_all_tags: bool, db: State<'_, DbState>) -> Result<Vec<NoteWithTags>> {
+    let db = db.pool();
+    let (predicate, binds) = build_search_predicate(&tags, match_all_tags, text.as_deref());
+
+    let query = format!(
+        "SELECT n.id, n.title, n.content, n.created_at, n.updated_at, n.is_pinned, n.is_favorite FROM notes n {}
+         ORDER BY n.created_at DESC",
+        predicate
+    );
+    let mut query_builder = sqlx::query_as::<_, Note>(&query);
+    for bind in &binds {
+        query_builder = match bind {
+            SearchBind::Text(value) => query_builder.bind(value),
+            SearchBind::Int(value) => query_builder.bind(value),
+        };
+    }
+    let notes = query_builder
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    attach_tags(notes, &db).await
+}
+
+enum SearchBind {
+    Text(String),
+    Int(i64),
+}
+
+// Helper function to build the WHERE clause (over `notes n`) for tag and text filters, limited to VISIBLE_NOTES.
+// With match_all every tag must be present; otherwise any one of them is enough.
+fn build_search_predicate(tag_names: &[String], match_all: bool, text: Option<&str>) -> (String, Vec<SearchBind>) {
+    let mut conditions = vec![VISIBLE_NOTES.to_string()];
+    let mut binds = Vec::new();
+
+    if !tag_names.is_empty() {
+        let placeholders = tag_names.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let having = if match_all { "HAVING COUNT(DISTINCT t.name) = ?" } else { "" };
+        conditions.push(format!(
+            "n.id IN (SELECT nt.note_id FROM note_tags nt
+                      INNER JOIN tags t ON nt.tag_id = t.id
+                      WHERE t.name COLLATE NOCASE IN ({})
+                      GROUP BY nt.note_id {})",
+            placeholders, having
+        ));
+        binds.extend(tag_names.iter().cloned().map(SearchBind::Text));
+        if match_all {
+            binds.push(SearchBind::Int(tag_names.len() as i64));
+        }
+    }
+
+    if let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) {
+        conditions.push("n.content LIKE ? ESCAPE '\\'".to_string());
+        binds.push(SearchBind::Text(format!("%{}%", escape_like(text))));
+    }
+
+    let predicate = format!("WHERE {}", conditions.join(" AND "));
+
+    (predicate, binds)
+}
+
+// Helper function to turn user input into an FTS5 query: every word is quoted so
+// operators and syntax like `foo*`, `-`, or `NEAR` are matched as plain text
+fn fts_match_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Helper function to turn FTS5 highlight() output, with matches wrapped in \x01 and \x02, into byte
+// ranges of the unmarked content
+fn match_spans(marked: &str) -> Vec<MatchSpan> {
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    let mut start = None;
+    for c in marked.chars() {
+        match c {
+            '\u{1}' => start = Some(offset),
+            '\u{2}' => {
+                if let Some(start) = start.take() {
+                    spans.push(MatchSpan { start, end: offset });
+                }
+            }
+            _ => offset += c.len_utf8(),
+        }
+    }
+    spans
+}
+
+// Helper function to escape LIKE wildcards so user text matches literally (paired with ESCAPE '\')
+pub(crate) fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Helper function to list (id, remind_at) for reminders that have come due
+pub(crate) async fn due_reminders(db: &Pool<Sqlite>) -> Result<Vec<(i64, i64)>> {
+    sqlx::query_as::<_, (i64, i64)>(
+        "SELECT id, remind_at FROM notes
+         WHERE remind_at IS NOT NULL AND remind_at <= ? AND deleted_at IS NULL"
+    )
+    .bind(now_millis())
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// Helper function to accept RFC 3339 or a bare UTC "YYYY-MM-DD HH:MM:SS", returned as epoch milliseconds
+fn parse_reminder_time(value: &str) -> Result<i64> {
+    let value = value.trim();
+    let utc = match chrono::DateTime::parse_from_rfc3339(value) {
+        Ok(time) => time.naive_utc(),
+        Err(_) => chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+            .map_err(|_| format!("Invalid reminder time: {}", value))?,
+    };
+    Ok(utc.and_utc().timestamp_millis())
+}
+
+// Helper function to insert a note and link its tags, reusing existing tag rows
+pub(crate) async fn insert_note_with_tags(conn: &mut SqliteConnection, content: &str, tags: &[String]) -> Result<i64> {
+    let note_id = sqlx::query("INSERT INTO notes (title, content) VALUES (?, ?)")
+        .bind(default_title(content))
+        .bind(content)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .last_insert_rowid();
+
+    link_tags(conn, note_id, tags).await?;
+
+    Ok(note_id)
+}
+
+// Helper function to link a note to tags by name, creating missing tags, with two statements per chunk of names.
+// Names match existing tags case-insensitively (ASCII only, as with SQLite's NOCASE) and keep the
+// spelling of whoever created the tag first, so "Work" followed by "work" both link to "Work".
+pub(crate) async fn link_tags(conn: &mut SqliteConnection, note_id: i64, tags: &[String]) -> Result<()> {
+    // Blank names never become tags
+    let mut names: Vec<String> = tags
+        .iter()
+        .map(|name| normalize_tag_name(name))
+        .filter(|name| !name.is_empty())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    // Chunked to stay well under SQLite's bound-parameter limit
+    for chunk in names.chunks(500) {
+        let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+
+        let values = chunk.iter().map(|_| "(?)").collect::<Vec<_>>().join(",");
+        let insert_tags = format!("INSERT OR IGNORE INTO tags (name) VALUES {}", values);
+        let mut query_builder = sqlx::query(&insert_tags);
+        for name in chunk {
+            query_builder = query_builder.bind(name);
+        }
+        query_builder
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let insert_links = format!(
+            "INSERT OR IGNORE INTO note_tags (note_id, tag_id) SELECT ?, id FROM tags WHERE name COLLATE NOCASE IN ({})",
+            placeholders
+        );
+        let mut query_builder = sqlx::query(&insert_links).bind(note_id);
+        for name in chunk {
+            query_builder = query_builder.bind(name);
+        }
+        query_builder
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+// Helper function to sort ids and drop repeats, so a note listed twice is only counted once
+fn dedup_ids(mut ids: Vec<i64>) -> Vec<i64> {
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+// Helper function to drop repeated ids while keeping the first occurrence of each in place
+fn dedup_ids_in_order(ids: Vec<i64>) -> Vec<i64> {
+    let mut seen = HashSet::new();
+    ids.into_iter().filter(|id| seen.insert(*id)).collect()
+}
+
+// Helper function to derive a title: the first non-empty line without heading markers, at most 100 chars
+pub(crate) fn default_title(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or("")
+        .chars()
+        .take(100)
+        .collect()
+}
+
+// Helper function to map a sort option to its ORDER BY terms
+fn sort_clause(sort_by: NoteSortOrder) -> &'static str {
+    match sort_by {
+        NoteSortOrder::CreatedDesc => "created_at DESC",
+        NoteSortOrder::CreatedAsc => "created_at ASC",
+        NoteSortOrder::UpdatedDesc => "updated_at DESC",
+        NoteSortOrder::UpdatedAsc => "updated_at ASC",
+    }
+}
+
+// Helper function for the current time in the epoch milliseconds stored in created_at/updated_at
+pub(crate) fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+// Helper function to use the given title, or derive one from the content when it is missing or blank
+fn resolve_title(title: Option<&str>, content: &str) -> String {
+    match title.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(title) => title.to_string(),
+        None => default_title(content),
+    }
+}
+
+// Helper function to save a note's current title and content as a revision, unless `new_content`
+// leaves the content unchanged, then prune revisions past MAX_REVISIONS_PER_NOTE
+async fn save_revision(conn: &mut SqliteConnection, note_id: i64, new_content: &str) -> Result<()> {
+    let saved = sqlx::query(
+        "INSERT INTO note_revisions (note_id, title, content, saved_at)
+         SELECT id, title, content, ? FROM notes WHERE id = ? AND content <> ?"
+    )
+    .bind(now_millis())
+    .bind(note_id)
+    .bind(new_content)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?
+    .rows_affected();
+    if saved == 0 {
+        return Ok(());
+    }
+
     sqlx::query(
-        "DELETE FROM tags WHERE id NOT IN (SELECT DISTINCT tag_id FROM note_tags)"
+        "DELETE FROM note_revisions
+         WHERE note_id = ? AND id NOT IN (
+             SELECT id FROM note_revisions WHERE note_id = ? ORDER BY saved_at DESC, id DESC LIMIT ?
+         )"
     )
-    .execute(db)
+    .bind(note_id)
+    .bind(note_id)
+    .bind(MAX_REVISIONS_PER_NOTE)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// Helper function to count a note's words (runs of non-whitespace, any Unicode whitespace separating),
+// characters and lines (a trailing newline doesn't start another line)
+fn note_stats(content: &str) -> NoteStats {
+    let words = content.split_whitespace().count() as u64;
+    NoteStats {
+        words,
+        characters: content.chars().count() as u64,
+        lines: content.lines().count() as u64,
+        reading_time_minutes: words.div_ceil(WORDS_PER_MINUTE),
+    }
+}
+
+// Helper function to flatten markdown to plain text, collapse whitespace and cut it to PREVIEW_CHARS
+fn markdown_preview(content: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new(content) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak | Event::HardBreak | Event::End(_) => text.push(' '),
+            _ => {}
+        }
+    }
+
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match collapsed.char_indices().nth(PREVIEW_CHARS) {
+        Some((cut, _)) => format!("{}…", collapsed[..cut].trim_end()),
+        None => collapsed,
+    }
+}
+
+// Helper function to tell every window that a note was created, updated or deleted.
+// The write is already committed, so a failed emit is only logged rather than failing the command.
+fn emit_notes_changed(app: &AppHandle, id: i64, kind: NoteChangeKind) {
+    if let Err(e) = app.emit("notes-changed", NotesChanged { id, kind }) {
+        eprintln!("Failed to emit note change: {}", e);
+    }
+}
+
+// Helper function to normalize content for comparison: unify line endings and ignore trailing whitespace
+pub(crate) fn normalize_content(content: &str) -> String {
+    content
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+// Helper function to load tags for each note in a list with one query per chunk of notes
+pub(crate) async fn attach_tags(notes: Vec<Note>, db: &Pool<Sqlite>) -> Result<Vec<NoteWithTags>> {
+    let mut tags_by_note: HashMap<i64, Vec<String>> = HashMap::new();
+
+    // Chunked to stay well under SQLite's bound-parameter limit
+    for chunk in notes.chunks(500) {
+        let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let query = format!(
+            "SELECT nt.note_id, t.name FROM tags t
+             INNER JOIN note_tags nt ON t.id = nt.tag_id
+             WHERE nt.note_id IN ({})
+             ORDER BY t.name",
+            placeholders
+        );
+        let mut query_builder = sqlx::query_as::<_, (i64, String)>(&query);
+        for note in chunk {
+            query_builder = query_builder.bind(note.id);
+        }
+        let rows = query_builder
+            .fetch_all(db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        for (note_id, name) in rows {
+            tags_by_note.entry(note_id).or_default().push(name);
+        }
+    }
+
+    Ok(notes
+        .into_iter()
+        .map(|note| NoteWithTags {
+            tags: tags_by_note.remove(&note.id).unwrap_or_default(),
+            id: note.id,
+            title: note.title,
+            content: note.content,
+            created_at: note.created_at,
+            updated_at: note.updated_at,
+            is_pinned: note.is_pinned,
+            is_favorite: note.is_favorite,
+        })
+        .collect())
+}
+
+// Helper function to flip the archived flag
+async fn set_archived(id: i64, archived: bool, db: &Pool<Sqlite>) -> Result<()> {
+    sqlx::query("UPDATE notes SET archived = ? WHERE id = ?")
+        .bind(archived)
+        .bind(id)
+        .execute(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// Helper function to get tags for a note
+async fn get_tags_for_note_internal(note_id: i64, db: &Pool<Sqlite>) -> Result<Vec<String>> {
+    let tags = sqlx::query_as::<_, (String,)>(
+        "SELECT t.name FROM tags t 
+         INNER JOIN note_tags nt ON t.id = nt.tag_id 
+         WHERE nt.note_id = ? 
+         ORDER BY t.name"
+    )
+    .bind(note_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(tags.into_iter().map(|(name,)| name).collect())
+}
+
+// Helper function to clean up orphaned tags; colored tags were set up on purpose and stay until deleted
+async fn cleanup_orphaned_tags(conn: &mut SqliteConnection) -> Result<()> {
+    sqlx::query(
+        "DELETE FROM tags WHERE color IS NULL AND id NOT IN (SELECT DISTINCT tag_id FROM note_tags)"
+    )
+    .execute(conn)
     .await
     .map_err(|e| e.to_string())?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dedup_ids_in_order, note_stats};
+
+    #[test]
+    fn note_stats_empty_and_blank() {
+        let stats = note_stats("");
+        assert_eq!((stats.words, stats.characters, stats.lines, stats.reading_time_minutes), (0, 0, 0, 0));
+
+        let stats = note_stats(" \t\n  ");
+        assert_eq!(stats.words, 0);
+        assert_eq!(stats.characters, 5);
+        assert_eq!(stats.reading_time_minutes, 0);
+    }
+
+    #[test]
+    fn note_stats_whitespace_runs() {
+        let stats = note_stats("one  \t two\n\n\tthree   four");
+        assert_eq!(stats.words, 4);
+        assert_eq!(stats.lines, 3);
+    }
+
+    #[test]
+    fn note_stats_line_endings() {
+        assert_eq!(note_stats("a\r\nb\r\nc").lines, 3);
+        assert_eq!(note_stats("a\nb\n").lines, 2);
+        assert_eq!(note_stats("a\r\nb\r\n").lines, 2);
+    }
+
+    #[test]
+    fn note_stats_counts_unicode_scalar_values() {
+        assert_eq!(note_stats("😀").characters, 1);
+        assert_eq!(note_stats("caf\u{e9}").characters, 4);
+        // "e" followed by a combining acute accent
+        assert_eq!(note_stats("cafe\u{301}").characters, 5);
+        assert_eq!(note_stats("😀 caf\u{e9}").words, 2);
+    }
+
+    #[test]
+    fn note_stats_reading_time() {
+        let words = |n: usize| vec!["word"; n].join(" ");
+        assert_eq!(note_stats(&words(0)).reading_time_minutes, 0);
+        assert_eq!(note_stats(&words(1)).reading_time_minutes, 1);
+        assert_eq!(note_stats(&words(200)).reading_time_minutes, 1);
+        assert_eq!(note_stats(&words(201)).reading_time_minutes, 2);
+    }
+
+    #[test]
+    fn dedup_ids_in_order_keeps_first_occurrence() {
+        assert_eq!(dedup_ids_in_order(vec![5, 5]), vec![5]);
+        assert_eq!(dedup_ids_in_order(vec![3, 1, 3, 2, 1]), vec![3, 1, 2]);
+        assert!(dedup_ids_in_order(Vec::new()).is_empty());
+    }
+}
@@ -1,14 +1,32 @@
 // src-tauri/src/api/plugins.rs
 
+use crate::api::settings::ensure_writable;
 use serde::{Deserialize, Serialize};
 use std::{fs, path::PathBuf};
-use tauri::{command, AppHandle, Manager};
+use tauri::{command, AppHandle, Emitter, Manager};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PluginManifest {
     pub name: String,
     pub version: String,
     pub description: String,
+    #[serde(default)]
+    pub min_app_version: Option<String>,
+    // Ids of other plugins that must be installed and enabled first
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    // JS and CSS entry files, relative to the plugin folder
+    #[serde(default)]
+    pub main: Option<String>,
+    #[serde(default)]
+    pub styles: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PluginCompatibility {
+    pub compatible: bool,
+    pub required_version: Option<String>,
+    pub app_version: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,11 +37,48 @@ pub struct PluginInfo {
     pub description: String,
     pub path: String,
     pub enabled: bool,
+    // Problems that don't stop the plugin from listing, such as a missing entry file
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PluginAssets {
+    pub main: Option<String>,
+    pub styles: Option<String>,
+}
+
+/// Payload of the `plugin-state-changed` event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PluginStateChanged {
+    pub plugin_id: String,
+    pub enabled: bool,
+}
+
+/// One entry of `list_plugins`: a loadable plugin, or a plugin folder whose manifest is broken
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum PluginListing {
+    Ok(PluginInfo),
+    Error { id: String, path: String, error: String },
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 struct PluginStateFile {
     enabled: std::collections::HashMap<String, bool>,
+    // Per-plugin settings, kept even while a plugin is disabled
+    #[serde(default)]
+    config: std::collections::HashMap<String, serde_json::Value>,
+}
+
+fn validate_plugin_id(plugin_id: &str) -> Result<(), String> {
+    if plugin_id.is_empty()
+        || plugin_id == "."
+        || plugin_id == ".."
+        || plugin_id.contains(['/', '\\'])
+    {
+        return Err(format!("Invalid plugin id: {}", plugin_id));
+    }
+    Ok(())
 }
 
 fn get_plugins_dir(app: &AppHandle) -> Result<PathBuf, String> {
@@ -62,7 +117,98 @@ fn read_state(app: &AppHandle) -> PluginStateFile {
 fn write_state(app: &AppHandle, state: &PluginStateFile) -> Result<(), String> {
     let path = get_state_file_path(app)?;
     let data = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
-    fs::write(path, data).map_err(|e| format!("Failed to write plugin state: {}", e))
+    // Write beside the real file and rename over it, so a crash mid-write can't truncate the state
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, data).map_err(|e| format!("Failed to write plugin state: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Failed to write plugin state: {}", e)
+    })
+}
+
+fn read_manifest(app: &AppHandle, plugin_id: &str) -> Result<PluginManifest, String> {
+    let manifest_path = get_plugins_dir(app)?.join(plugin_id).join("plugin.json");
+    if !manifest_path.exists() {
+        return Err(format!("Plugin not found: {}", plugin_id));
+    }
+    parse_manifest(&manifest_path)
+}
+
+// Read and validate a plugin.json: it must deserialize, name a plugin and carry a semver version
+fn parse_manifest(manifest_path: &std::path::Path) -> Result<PluginManifest, String> {
+    let content = fs::read_to_string(manifest_path)
+        .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+    let manifest: PluginManifest = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid plugin.json: {}", e))?;
+
+    if manifest.name.trim().is_empty() {
+        return Err("Invalid plugin.json: name must not be empty".to_string());
+    }
+    semver::Version::parse(manifest.version.trim())
+        .map_err(|e| format!("Invalid plugin.json: version '{}' is not semver: {}", manifest.version, e))?;
+
+    Ok(manifest)
+}
+
+// Resolve a manifest entry file inside the plugin folder, refusing paths that climb out of it
+fn resolve_asset(plugin_dir: &std::path::Path, file: &str) -> Result<PathBuf, String> {
+    let relative = std::path::Path::new(file);
+    let escapes = relative.components().any(|c| {
+        !matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir)
+    });
+    if file.is_empty() || escapes {
+        return Err(format!("Invalid plugin file path: {}", file));
+    }
+    Ok(plugin_dir.join(relative))
+}
+
+// Entry files the manifest declares but the plugin folder doesn't contain
+fn asset_warnings(plugin_dir: &std::path::Path, manifest: &PluginManifest) -> Vec<String> {
+    [("main", &manifest.main), ("styles", &manifest.styles)]
+        .into_iter()
+        .filter_map(|(field, file)| {
+            let file = file.as_ref()?;
+            match resolve_asset(plugin_dir, file) {
+                Ok(asset) if asset.is_file() => None,
+                Ok(_) => Some(format!("{} file not found: {}", field, file)),
+                Err(e) => Some(e),
+            }
+        })
+        .collect()
+}
+
+// Unpack `zip_path` into `dest` and check it holds a valid plugin.json at its root
+fn extract_plugin(zip_path: &std::path::Path, dest: &std::path::Path) -> Result<PluginManifest, String> {
+    let file = fs::File::open(zip_path)
+        .map_err(|e| format!("Failed to open {}: {}", zip_path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(std::io::BufReader::new(file))
+        .map_err(|e| format!("Invalid plugin archive: {}", e))?;
+    // extract() rejects entries whose paths would land outside `dest`
+    archive
+        .extract(dest)
+        .map_err(|e| format!("Failed to extract plugin archive: {}", e))?;
+
+    let manifest_path = dest.join("plugin.json");
+    if !manifest_path.is_file() {
+        return Err("Invalid plugin archive: no plugin.json at the archive root".to_string());
+    }
+    parse_manifest(&manifest_path)
+}
+
+fn check_compatibility(app: &AppHandle, manifest: &PluginManifest) -> Result<PluginCompatibility, String> {
+    let app_version = &app.package_info().version;
+    // A bare "0.2" reads as ">=0.2", so manifests don't need a full semver triple
+    let compatible = match &manifest.min_app_version {
+        Some(min) => semver::VersionReq::parse(&format!(">={}", min.trim()))
+            .map_err(|e| format!("Invalid min_app_version '{}' in {}: {}", min, manifest.name, e))?
+            .matches(app_version),
+        None => true,
+    };
+    Ok(PluginCompatibility {
+        compatible,
+        required_version: manifest.min_app_version.clone(),
+        app_version: app_version.to_string(),
+    })
 }
 
 #[command]
@@ -99,10 +245,11 @@ pub async fn open_plugins_directory(app: AppHandle) -> Result<(), String> {
 }
 
 #[command]
-pub async fn list_plugins(app: AppHandle) -> Result<Vec<PluginInfo>, String> {
+pub async fn list_plugins(app: AppHandle) -> Result<Vec<PluginListing>, String> {
     let plugins_dir = get_plugins_dir(&app)?;
-    let state = read_state(&app);
+    let mut state = read_state(&app);
 
+    let mut present = std::collections::HashSet::new();
     let mut plugins = Vec::new();
     let entries = fs::read_dir(&plugins_dir)
         .map_err(|e| format!("Failed to read plugins directory: {}", e))?;
@@ -115,36 +262,242 @@ pub async fn list_plugins(app: AppHandle) -> Result<Vec<PluginInfo>, String> {
                 .and_then(|s| s.to_str())
                 .unwrap_or("unknown")
                 .to_string();
+            // Hidden folders are in-progress installs, not plugins
+            if id.starts_with('.') {
+                continue;
+            }
+            present.insert(id.clone());
             let manifest_path = path.join("plugin.json");
             if manifest_path.exists() {
-                let content = fs::read_to_string(&manifest_path)
-                    .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
-                let manifest: PluginManifest = serde_json::from_str(&content)
-                    .map_err(|e| format!("Invalid plugin.json in {}: {}", id, e))?;
+                // A broken manifest is reported for its own plugin instead of failing the whole list
+                let manifest = match parse_manifest(&manifest_path) {
+                    Ok(manifest) => manifest,
+                    Err(error) => {
+                        plugins.push(PluginListing::Error {
+                            id,
+                            path: path.to_string_lossy().to_string(),
+                            error,
+                        });
+                        continue;
+                    }
+                };
 
+                let warnings = asset_warnings(&path, &manifest);
                 let enabled = state.enabled.get(&id).copied().unwrap_or(false);
-                plugins.push(PluginInfo {
+                plugins.push(PluginListing::Ok(PluginInfo {
                     id,
                     name: manifest.name,
                     version: manifest.version,
                     description: manifest.description,
                     path: path.to_string_lossy().to_string(),
                     enabled,
-                });
+                    warnings,
+                }));
             }
         }
     }
 
-    // sort by name
-    plugins.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    // Drop enabled flags for plugins deleted from disk so a reinstall starts disabled
+    let known = state.enabled.len();
+    state.enabled.retain(|id, _| present.contains(id));
+    if state.enabled.len() != known {
+        write_state(&app, &state)?;
+    }
+
+    // sort by name, with broken plugins sorted by their folder name
+    plugins.sort_by_key(|plugin| match plugin {
+        PluginListing::Ok(info) => info.name.to_lowercase(),
+        PluginListing::Error { id, .. } => id.to_lowercase(),
+    });
     Ok(plugins)
 }
 
+/// Install a plugin from a `.zip`, using the archive's file name as the plugin id
 #[command]
-pub async fn set_plugin_enabled(app: AppHandle, plugin_id: String, enabled: bool) -> Result<(), String> {
+pub async fn install_plugin(
+    app: AppHandle,
+    zip_path: String,
+    overwrite: Option<bool>,
+) -> Result<PluginInfo, String> {
+    ensure_writable(&app)?;
+    let zip_path = PathBuf::from(zip_path);
+    let plugin_id = zip_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    validate_plugin_id(&plugin_id)?;
+
+    let plugins_dir = get_plugins_dir(&app)?;
+    let target = plugins_dir.join(&plugin_id);
+    if target.exists() && !overwrite.unwrap_or(false) {
+        return Err(format!("Plugin '{}' is already installed", plugin_id));
+    }
+
+    // Extract next to the target so the final rename stays on one filesystem
+    let staging = plugins_dir.join(format!(".{}.installing", plugin_id));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .map_err(|e| format!("Failed to clear {}: {}", staging.display(), e))?;
+    }
+    let manifest = match extract_plugin(&zip_path, &staging) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+
+    let installed = (|| {
+        if target.exists() {
+            fs::remove_dir_all(&target)
+                .map_err(|e| format!("Failed to remove existing plugin '{}': {}", plugin_id, e))?;
+        }
+        fs::rename(&staging, &target)
+            .map_err(|e| format!("Failed to install plugin '{}': {}", plugin_id, e))
+    })();
+    if let Err(e) = installed {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    let enabled = read_state(&app).enabled.get(&plugin_id).copied().unwrap_or(false);
+    Ok(PluginInfo {
+        warnings: asset_warnings(&target, &manifest),
+        id: plugin_id,
+        name: manifest.name,
+        version: manifest.version,
+        description: manifest.description,
+        path: target.to_string_lossy().to_string(),
+        enabled,
+    })
+}
+
+#[command]
+pub async fn uninstall_plugin(app: AppHandle, plugin_id: String) -> Result<(), String> {
+    ensure_writable(&app)?;
+    validate_plugin_id(&plugin_id)?;
+
+    let plugins_dir = get_plugins_dir(&app)?;
+    let plugin_dir = plugins_dir.join(&plugin_id);
+    if !plugin_dir.is_dir() {
+        return Err(format!("Plugin not found: {}", plugin_id));
+    }
+    // Resolve symlinks so only a real folder directly inside the plugins dir is ever removed
+    let resolved_parent = plugin_dir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve plugin path: {}", e))?
+        .parent()
+        .map(|p| p.to_path_buf());
+    let resolved_plugins_dir = plugins_dir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve plugins directory: {}", e))?;
+    if resolved_parent.as_ref() != Some(&resolved_plugins_dir) {
+        return Err(format!("Plugin not found: {}", plugin_id));
+    }
+
+    fs::remove_dir_all(&plugin_dir)
+        .map_err(|e| format!("Failed to remove plugin '{}': {}", plugin_id, e))?;
+
     let mut state = read_state(&app);
-    state.enabled.insert(plugin_id, enabled);
+    state.enabled.remove(&plugin_id);
+    state.config.remove(&plugin_id);
     write_state(&app, &state)
 }
 
+#[command]
+pub async fn set_plugin_enabled(app: AppHandle, plugin_id: String, enabled: bool) -> Result<(), String> {
+    validate_plugin_id(&plugin_id)?;
+
+    // Disabling is always allowed; enabling requires a compatible app version and enabled dependencies
+    if enabled {
+        let manifest = read_manifest(&app, &plugin_id)?;
+        let compatibility = check_compatibility(&app, &manifest)?;
+        if !compatibility.compatible {
+            return Err(format!(
+                "{} requires Lychee {} or newer (running {})",
+                manifest.name,
+                compatibility.required_version.unwrap_or_default(),
+                compatibility.app_version
+            ));
+        }
+
+        let state = read_state(&app);
+        for dependency in &manifest.dependencies {
+            let available = validate_plugin_id(dependency).is_ok()
+                && read_manifest(&app, dependency).is_ok()
+                && state.enabled.get(dependency).copied().unwrap_or(false);
+            if !available {
+                return Err(format!(
+                    "{} requires the plugin '{}' to be installed and enabled",
+                    manifest.name, dependency
+                ));
+            }
+        }
+    }
+
+    let mut state = read_state(&app);
+    state.enabled.insert(plugin_id.clone(), enabled);
+    write_state(&app, &state)?;
+
+    // The change is already saved, so a failed emit is only logged rather than failing the command
+    if let Err(e) = app.emit("plugin-state-changed", PluginStateChanged { plugin_id, enabled }) {
+        eprintln!("Failed to emit plugin state change: {}", e);
+    }
+    Ok(())
+}
+
+#[command]
+pub async fn check_plugin_compatibility(app: AppHandle, plugin_id: String) -> Result<PluginCompatibility, String> {
+    validate_plugin_id(&plugin_id)?;
+    let manifest = read_manifest(&app, &plugin_id)?;
+    check_compatibility(&app, &manifest)
+}
+
+/// Contents of the plugin's `main` and `styles` files, for plugins that declare them
+#[command]
+pub async fn get_plugin_assets(app: AppHandle, plugin_id: String) -> Result<PluginAssets, String> {
+    validate_plugin_id(&plugin_id)?;
+    let manifest = read_manifest(&app, &plugin_id)?;
+    let plugin_dir = get_plugins_dir(&app)?.join(&plugin_id);
+
+    let read_asset = |file: &Option<String>| -> Result<Option<String>, String> {
+        let Some(file) = file else {
+            return Ok(None);
+        };
+        let path = resolve_asset(&plugin_dir, file)?;
+        fs::read_to_string(&path)
+            .map(Some)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    };
+
+    Ok(PluginAssets {
+        main: read_asset(&manifest.main)?,
+        styles: read_asset(&manifest.styles)?,
+    })
+}
+
+#[command]
+pub async fn get_plugin_config(app: AppHandle, plugin_id: String) -> Result<serde_json::Value, String> {
+    validate_plugin_id(&plugin_id)?;
+    let state = read_state(&app);
+    Ok(state
+        .config
+        .get(&plugin_id)
+        .cloned()
+        .unwrap_or_else(|| serde_json::json!({})))
+}
 
+#[command]
+pub async fn set_plugin_config(
+    app: AppHandle,
+    plugin_id: String,
+    config: serde_json::Value,
+) -> Result<(), String> {
+    ensure_writable(&app)?;
+    validate_plugin_id(&plugin_id)?;
+    let mut state = read_state(&app);
+    state.config.insert(plugin_id, config);
+    write_state(&app, &state)
+}
@@ -0,0 +1,26 @@
+// src-tauri/src/api/render.rs
+
+use crate::api::settings::read_settings;
+use crate::api::themes::read_theme_css;
+use crate::core::markdown::render_html;
+use tauri::{command, AppHandle};
+
+/// Render unsaved markdown to HTML, inlining a theme's CSS when one is given
+#[command]
+pub async fn render_markdown_preview(
+    app: AppHandle,
+    content: String,
+    theme_name: Option<String>,
+    sanitize: Option<bool>,
+) -> Result<String, String> {
+    let options = read_settings(&app).markdown_options;
+    let body = render_html(&content, sanitize.unwrap_or(true), &options);
+
+    match theme_name {
+        Some(name) => {
+            let css = read_theme_css(&app, &name)?;
+            Ok(format!("<style>\n{}\n</style>\n{}", css, body))
+        }
+        None => Ok(body),
+    }
+}
@@ -0,0 +1,124 @@
+// src-tauri/src/api/settings.rs
+
+use crate::core::markdown::MarkdownOptions;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+use tauri::{command, AppHandle, Manager};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub active_theme: Option<String>,
+    pub markdown_options: MarkdownOptions,
+    pub read_only: bool,
+    // When set, the database stays locked at startup until `unlock_database` supplies the passphrase
+    pub database_encrypted: bool,
+    // Where the default workspace's database lives instead of app data/tree.db; read at startup
+    pub database_path: Option<String>,
+}
+
+fn get_settings_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    Ok(app_data_dir.join("settings.json"))
+}
+
+pub(crate) fn read_settings(app: &AppHandle) -> Settings {
+    let path = match get_settings_file_path(app) {
+        Ok(p) => p,
+        Err(_) => return Settings::default(),
+    };
+    if !path.exists() {
+        return Settings::default();
+    }
+    let data = fs::read_to_string(path).unwrap_or_default();
+    serde_json::from_str(&data).unwrap_or_default()
+}
+
+pub(crate) fn write_settings(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let path = get_settings_file_path(app)?;
+    let data = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(path, data).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+// Settings with their own commands, which validate them or act on the database before saving
+const MANAGED_SETTINGS: [(&str, &str); 2] = [
+    ("database_encrypted", "set_database_passphrase"),
+    ("database_path", "set_database_location"),
+];
+
+// Recursively copy `patch` into `target`, so nested objects can be patched one field at a time
+fn merge_json(target: &mut serde_json::Value, patch: serde_json::Value) {
+    match (target, patch) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge_json(target.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// Every command that writes user content calls this first: notes, tags, snippets, imports, restores,
+/// and the theme and plugin files. Left writable on purpose: settings and preferences (active theme,
+/// enabled plugins), since `set_read_only` itself lives there; workspaces, unlocking and the database
+/// location, which choose what is open rather than change it; and exports, backups and vacuum, which
+/// only copy or compact the data.
+pub(crate) fn ensure_writable(app: &AppHandle) -> Result<(), String> {
+    if read_settings(app).read_only {
+        return Err("App is in read-only mode".to_string());
+    }
+    Ok(())
+}
+
+#[command]
+pub async fn is_read_only(app: AppHandle) -> Result<bool, String> {
+    Ok(read_settings(&app).read_only)
+}
+
+#[command]
+pub async fn set_read_only(app: AppHandle, value: bool) -> Result<(), String> {
+    let mut settings = read_settings(&app);
+    settings.read_only = value;
+    write_settings(&app, &settings)
+}
+
+#[command]
+pub async fn get_markdown_options(app: AppHandle) -> Result<MarkdownOptions, String> {
+    Ok(read_settings(&app).markdown_options)
+}
+
+#[command]
+pub async fn set_markdown_options(app: AppHandle, options: MarkdownOptions) -> Result<(), String> {
+    let mut settings = read_settings(&app);
+    settings.markdown_options = options;
+    write_settings(&app, &settings)
+}
+
+#[command]
+pub async fn get_settings(app: AppHandle) -> Result<Settings, String> {
+    Ok(read_settings(&app))
+}
+
+/// Apply a partial settings object, e.g. `{ "markdown_options": { "tables": false } }`, and return the result
+#[command]
+pub async fn update_settings(app: AppHandle, partial: serde_json::Value) -> Result<Settings, String> {
+    let serde_json::Value::Object(patch) = &partial else {
+        return Err("Settings update must be an object".to_string());
+    };
+    for (key, command) in MANAGED_SETTINGS {
+        if patch.contains_key(key) {
+            return Err(format!("'{}' can only be changed with {}", key, command));
+        }
+    }
+
+    let mut merged = serde_json::to_value(read_settings(&app)).map_err(|e| e.to_string())?;
+    merge_json(&mut merged, partial);
+    let settings: Settings = serde_json::from_value(merged)
+        .map_err(|e| format!("Invalid settings: {}", e))?;
+
+    write_settings(&app, &settings)?;
+    Ok(settings)
+}
@@ -0,0 +1,67 @@
+// src-tauri/src/api/snippets.rs
+
+use crate::api::settings::ensure_writable;
+use crate::core::db::DbState;
+use crate::core::models::Snippet;
+use tauri::{AppHandle, State};
+
+type Result<T> = std::result::Result<T, String>;
+
+#[tauri::command]
+pub async fn create_snippet(trigger: String, content: String, app: AppHandle, db: State<'_, DbState>) -> Result<i64> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let trigger = trigger.trim().to_string();
+    if trigger.is_empty() {
+        return Err("Snippet trigger must not be empty".to_string());
+    }
+
+    let res = sqlx::query("INSERT INTO snippets (trigger, content) VALUES (?, ?)")
+        .bind(&trigger)
+        .bind(&content)
+        .execute(&db)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+                format!("A snippet with trigger '{}' already exists", trigger)
+            }
+            _ => e.to_string(),
+        })?;
+
+    Ok(res.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn list_snippets(db: State<'_, DbState>) -> Result<Vec<Snippet>> {
+    let db = db.pool();
+    let snippets = sqlx::query_as::<_, Snippet>("SELECT id, trigger, content FROM snippets ORDER BY trigger")
+        .fetch_all(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(snippets)
+}
+
+#[tauri::command]
+pub async fn delete_snippet(id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    sqlx::query("DELETE FROM snippets WHERE id = ?")
+        .bind(id)
+        .execute(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn expand_snippet(trigger: String, db: State<'_, DbState>) -> Result<Option<String>> {
+    let db = db.pool();
+    let content = sqlx::query_as::<_, (String,)>("SELECT content FROM snippets WHERE trigger = ?")
+        .bind(trigger.trim())
+        .fetch_optional(&db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(content.map(|(content,)| content))
+}
@@ -0,0 +1,84 @@
+// src-tauri/src/api/storage.rs
+
+use crate::api::workspaces::active_database_path;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{command, AppHandle, Manager};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageBreakdown {
+    pub database: u64,
+    pub workspaces: u64,
+    pub themes: u64,
+    pub plugins: u64,
+    pub exports: u64,
+    pub attachments: u64,
+    pub backups: u64,
+    pub total: u64,
+}
+
+/// Bytes used on disk by each part of the app data directory
+#[command]
+pub async fn get_storage_breakdown(app: AppHandle) -> Result<StorageBreakdown, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+
+    // SQLite keeps recent writes in -wal/-shm side files until checkpointed
+    let db_path = active_database_path(&app)?;
+    let database: u64 = ["", "-wal", "-shm"]
+        .iter()
+        .map(|suffix| {
+            let mut path = db_path.clone().into_os_string();
+            path.push(suffix);
+            file_size(Path::new(&path))
+        })
+        .sum();
+
+    // Don't count a non-default active workspace twice
+    let workspaces_dir = app_data_dir.join("workspaces");
+    let mut workspaces = dir_size(&workspaces_dir);
+    if db_path.starts_with(&workspaces_dir) {
+        workspaces = workspaces.saturating_sub(database);
+    }
+    let themes = dir_size(&app_data_dir.join("themes"));
+    let plugins = dir_size(&app_data_dir.join("plugins"));
+    let exports = dir_size(&app_data_dir.join("exports"));
+    let attachments = dir_size(&app_data_dir.join("attachments"));
+    let backups = dir_size(&app_data_dir.join("backups"));
+
+    Ok(StorageBreakdown {
+        database,
+        workspaces,
+        themes,
+        plugins,
+        exports,
+        attachments,
+        backups,
+        total: database + workspaces + themes + plugins + exports + attachments + backups,
+    })
+}
+
+// Helper functions
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+// Recursively sum file sizes; missing directories count as empty
+fn dir_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.file_type() {
+            Ok(ft) if ft.is_dir() => dir_size(&entry.path()),
+            Ok(ft) if ft.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
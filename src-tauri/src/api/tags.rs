@@ -1,47 +1,339 @@
 // src-tauri/src/api/tags.rs
 
-use crate::core::models::Tag;
-use tauri::State;
-use sqlx::{Pool, Sqlite};
+use crate::core::models::{Tag, TagWithCount, TagInsight, TagStorage, RepairReport};
+use crate::core::db::DbState;
+use crate::api::notes::escape_like;
+use crate::api::settings::ensure_writable;
+use tauri::{AppHandle, State};
+use sqlx::SqliteConnection;
 
 type Result<T> = std::result::Result<T, String>;
 
 #[tauri::command]
-pub async fn get_all_tags(db: State<'_, Pool<Sqlite>>) -> Result<Vec<Tag>> {
-    let tags = sqlx::query_as::<_, Tag>("SELECT id, name FROM tags ORDER BY name")
-        .fetch_all(&*db)
+pub async fn get_all_tags(db: State<'_, DbState>) -> Result<Vec<Tag>> {
+    let db = db.pool();
+    let tags = sqlx::query_as::<_, Tag>("SELECT id, name, color FROM tags ORDER BY name")
+        .fetch_all(&db)
         .await
         .map_err(|e| e.to_string())?;
     Ok(tags)
 }
 
 #[tauri::command]
-pub async fn delete_tag(tag_id: i64, db: State<'_, Pool<Sqlite>>) -> Result<()> {
-    // First delete all note_tags associations
-    sqlx::query("DELETE FROM note_tags WHERE tag_id = ?")
+pub async fn get_tags_with_counts(db: State<'_, DbState>) -> Result<Vec<TagWithCount>> {
+    let db = db.pool();
+    // Every linked note counts, trashed ones included, since deleting the tag unlinks them all
+    let tags = sqlx::query_as::<_, TagWithCount>(
+        "SELECT t.id, t.name, COUNT(nt.note_id) AS note_count
+         FROM tags t
+         LEFT JOIN note_tags nt ON t.id = nt.tag_id
+         GROUP BY t.id, t.name
+         ORDER BY t.name"
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn suggest_tags(prefix: String, limit: i64, db: State<'_, DbState>) -> Result<Vec<String>> {
+    let db = db.pool();
+    // LIKE is case-insensitive for ASCII; the prefix is escaped so % and _ match literally
+    let suggestions = sqlx::query_as::<_, (String,)>(
+        "SELECT t.name
+         FROM tags t
+         LEFT JOIN note_tags nt ON t.id = nt.tag_id
+         WHERE t.name LIKE ? ESCAPE '\\'
+         GROUP BY t.id, t.name
+         ORDER BY COUNT(nt.note_id) DESC, t.name
+         LIMIT ?"
+    )
+    .bind(format!("{}%", escape_like(prefix.trim())))
+    .bind(limit.max(0))
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(suggestions.into_iter().map(|(name,)| name).collect())
+}
+
+#[tauri::command]
+pub async fn get_tag_insights(db: State<'_, DbState>) -> Result<Vec<TagInsight>> {
+    let db = db.pool();
+    // Usage count and most recent note activity per tag, so the frontend can rank either way
+    let insights = sqlx::query_as::<_, TagInsight>(
+        "SELECT t.id, t.name, COUNT(n.id) AS count, MAX(n.updated_at) AS last_used
+         FROM tags t
+         LEFT JOIN note_tags nt ON t.id = nt.tag_id
+         LEFT JOIN notes n ON nt.note_id = n.id
+         GROUP BY t.id, t.name
+         ORDER BY last_used DESC, count DESC, t.name"
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(insights)
+}
+
+#[tauri::command]
+pub async fn get_storage_by_tag(db: State<'_, DbState>) -> Result<Vec<TagStorage>> {
+    let db = db.pool();
+    // A note under several tags counts toward each of them
+    let storage = sqlx::query_as::<_, TagStorage>(
+        "SELECT t.name AS tag, COALESCE(SUM(LENGTH(n.content)), 0) AS total_chars, COUNT(n.id) AS note_count
+         FROM tags t
+         INNER JOIN note_tags nt ON t.id = nt.tag_id
+         INNER JOIN notes n ON nt.note_id = n.id
+         GROUP BY t.id, t.name
+         ORDER BY total_chars DESC, t.name"
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(storage)
+}
+
+#[tauri::command]
+pub async fn delete_tag(tag_id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    // Deleting the tag removes its note_tags associations through ON DELETE CASCADE
+    sqlx::query("DELETE FROM tags WHERE id = ?")
         .bind(tag_id)
-        .execute(&*db)
+        .execute(&db)
         .await
         .map_err(|e| e.to_string())?;
 
-    // Then delete the tag itself
-    sqlx::query("DELETE FROM tags WHERE id = ?")
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn rename_tag(tag_id: i64, new_name: String, merge_on_conflict: Option<bool>, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let new_name = normalize_tag_name(&new_name);
+    if new_name.is_empty() {
+        return Err("Tag name must not be empty".to_string());
+    }
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    let existing = sqlx::query_as::<_, (i64,)>("SELECT id FROM tags WHERE name = ? COLLATE NOCASE AND id != ?")
+        .bind(&new_name)
         .bind(tag_id)
-        .execute(&*db)
+        .fetch_optional(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
 
+    match existing {
+        Some((target_id,)) if merge_on_conflict.unwrap_or(false) => {
+            merge_tag_into(&mut tx, tag_id, target_id).await?;
+        }
+        Some(_) => return Err(format!("A tag named '{}' already exists", new_name)),
+        None => {
+            let renamed = sqlx::query("UPDATE tags SET name = ? WHERE id = ?")
+                .bind(&new_name)
+                .bind(tag_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?
+                .rows_affected();
+            if renamed == 0 {
+                return Err(format!("Tag not found: {}", tag_id));
+            }
+        }
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
     Ok(())
 }
 
 #[tauri::command]
-pub async fn rename_tag(tag_id: i64, new_name: String, db: State<'_, Pool<Sqlite>>) -> Result<()> {
-    sqlx::query("UPDATE tags SET name = ? WHERE id = ?")
-        .bind(&new_name)
+pub async fn set_tag_color(tag_id: i64, color: Option<String>, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    // None clears the color
+    let color = color.as_deref().map(normalize_hex_color).transpose()?;
+    let res = sqlx::query("UPDATE tags SET color = ? WHERE id = ?")
+        .bind(&color)
         .bind(tag_id)
-        .execute(&*db)
+        .execute(&db)
         .await
         .map_err(|e| e.to_string())?;
 
+    if res.rows_affected() == 0 {
+        return Err(format!("Tag not found: {}", tag_id));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn rename_tags_by_prefix(old_prefix: String, new_prefix: String, app: AppHandle, db: State<'_, DbState>) -> Result<usize> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    if old_prefix.is_empty() {
+        return Err("Prefix to replace must not be empty".to_string());
+    }
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    // Literal prefix match; LIKE would treat % and _ in the prefix as wildcards
+    let tags = sqlx::query_as::<_, Tag>(
+        "SELECT id, name, color FROM tags WHERE substr(name, 1, length(?)) = ? ORDER BY name"
+    )
+    .bind(&old_prefix)
+    .bind(&old_prefix)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut affected = 0;
+    for tag in tags {
+        // Same rules as rename_tag, so an empty new prefix can't leave a blank name
+        let new_name = normalize_tag_name(&format!("{}{}", new_prefix, &tag.name[old_prefix.len()..]));
+        if new_name.is_empty() {
+            return Err(format!("Renaming '{}' would leave an empty tag name", tag.name));
+        }
+        if new_name == tag.name {
+            continue;
+        }
+
+        let existing = sqlx::query_as::<_, (i64,)>("SELECT id FROM tags WHERE name = ? COLLATE NOCASE AND id != ?")
+            .bind(&new_name)
+            .bind(tag.id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match existing {
+            Some((target_id,)) => merge_tag_into(&mut tx, tag.id, target_id).await?,
+            None => {
+                sqlx::query("UPDATE tags SET name = ? WHERE id = ?")
+                    .bind(&new_name)
+                    .bind(tag.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        affected += 1;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(affected)
+}
+
+#[tauri::command]
+pub async fn merge_tags(source_tag_id: i64, target_tag_id: i64, app: AppHandle, db: State<'_, DbState>) -> Result<()> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    if source_tag_id == target_tag_id {
+        return Err("Cannot merge a tag into itself".to_string());
+    }
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    for id in [source_tag_id, target_tag_id] {
+        let exists = sqlx::query_as::<_, (i64,)>("SELECT id FROM tags WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        if exists.is_none() {
+            return Err(format!("Tag not found: {}", id));
+        }
+    }
+
+    merge_tag_into(&mut tx, source_tag_id, target_tag_id).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn repair_associations(app: AppHandle, db: State<'_, DbState>) -> Result<RepairReport> {
+    ensure_writable(&app)?;
+    let db = db.pool();
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    // Rows left behind while foreign keys were not enforced
+    let dangling_note_links = sqlx::query("DELETE FROM note_tags WHERE note_id NOT IN (SELECT id FROM notes)")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+
+    let dangling_tag_links = sqlx::query("DELETE FROM note_tags WHERE tag_id NOT IN (SELECT id FROM tags)")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+
+    // Colored tags are kept without notes, like cleanup_orphaned_tags does
+    let orphaned_tags = sqlx::query("DELETE FROM tags WHERE color IS NULL AND id NOT IN (SELECT DISTINCT tag_id FROM note_tags)")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(RepairReport {
+        dangling_note_links,
+        dangling_tag_links,
+        orphaned_tags,
+    })
+}
+
+// Helper function to trim a tag name and collapse inner runs of whitespace to single spaces
+pub(crate) fn normalize_tag_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Helper function to validate a "#rgb" or "#rrggbb" color, returning it as lowercase "#rrggbb"
+pub(crate) fn normalize_hex_color(color: &str) -> Result<String> {
+    let invalid = || format!("Invalid color '{}': expected a hex code like #ff6766", color);
+    let digits = color.trim().strip_prefix('#').ok_or_else(invalid)?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let digits = digits.to_ascii_lowercase();
+    match digits.len() {
+        6 => Ok(format!("#{}", digits)),
+        3 => Ok(format!("#{}", digits.chars().flat_map(|c| [c, c]).collect::<String>())),
+        _ => Err(invalid()),
+    }
+}
+
+// Helper function to move every note from one tag onto another and drop the source tag
+async fn merge_tag_into(conn: &mut SqliteConnection, source_id: i64, target_id: i64) -> Result<()> {
+    // Notes that already carry the target tag are skipped by OR IGNORE
+    sqlx::query(
+        "INSERT OR IGNORE INTO note_tags (note_id, tag_id)
+         SELECT note_id, ? FROM note_tags WHERE tag_id = ?"
+    )
+    .bind(target_id)
+    .bind(source_id)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // The source tag's remaining links cascade away with it
+    let deleted = sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(source_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(format!("Tag not found: {}", source_id));
+    }
+
     Ok(())
 }
@@ -3,8 +3,14 @@
  * Handles user theme creation, loading, and directory management
  */
 
+use crate::api::settings::{ensure_writable, read_settings, write_settings};
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
 use serde::{Deserialize, Serialize};
-use tauri::{command, AppHandle, Manager};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{command, AppHandle, Emitter, Manager};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserTheme {
@@ -12,6 +18,18 @@ pub struct UserTheme {
     pub display_name: String,
     pub content: String,
     pub file_path: String,
+    /// Uses a default theme's file name but no longer has the default's CSS
+    pub shadows_default: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParsedUserTheme {
+    pub name: String,
+    pub display_name: String,
+    pub content: String,
+    pub file_path: String,
+    /// Custom property name (e.g. `--color-primary`) to its value
+    pub variables: HashMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,6 +39,63 @@ pub struct ThemeInfo {
     pub is_user_theme: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThemeNameAvailability {
+    pub available: bool,
+    pub sanitized_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThemeVariantToggle {
+    pub theme: String,
+    pub toggled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThemeValidation {
+    pub valid: bool,
+    pub missing_variables: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Variables every theme must define, matching the set written by `initialize_default_themes`
+const REQUIRED_THEME_VARIABLES: &[&str] = &[
+    "--color-primary",
+    "--color-background-primary",
+    "--color-background-secondary",
+    "--color-text-primary",
+    "--color-text-secondary",
+    "--color-border",
+    "--color-success",
+    "--color-danger",
+];
+
+/// Keeps the themes directory watcher alive for as long as the app is managed
+pub struct ThemesWatcher {
+    _debouncer: Mutex<Debouncer<RecommendedWatcher>>,
+}
+
+/// Light/dark counterparts that `toggle_theme_variant` swaps between
+const THEME_VARIANT_PAIRS: &[(&str, &str)] = &[
+    ("light", "dark"),
+    ("lychee-classic", "lychee-dark"),
+];
+
+/// File stems of the themes written by `initialize_default_themes`
+const DEFAULT_THEME_NAMES: &[&str] = &[
+    "vivid-night",
+    "purple-haze",
+    "seaside-picnic",
+    "sunset-savannah",
+    "modern-camo",
+    "pastel-sunrise",
+    "lychee-classic",
+    "lychee-dark",
+    "cloudy-thoughts",
+    "blackberry-fizz",
+    "bleeding-heart",
+];
+
 /// Get the themes directory path in app data
 #[command]
 pub async fn get_themes_directory(app: AppHandle) -> Result<String, String> {
@@ -70,17 +145,10 @@ pub async fn get_user_themes(app: AppHandle) -> Result<Vec<UserTheme>, String> {
                 .unwrap_or("unknown")
                 .to_string();
             
-            let display_name = capitalize_words(&name);
-            
             let content = std::fs::read_to_string(&path)
                 .map_err(|e| format!("Failed to read theme file {}: {}", path.display(), e))?;
             
-            themes.push(UserTheme {
-                name: format!("user-{}", name),
-                display_name,
-                content,
-                file_path: path.to_string_lossy().to_string(),
-            });
+            themes.push(user_theme(&name, content, &path));
         }
     }
     
@@ -90,6 +158,59 @@ pub async fn get_user_themes(app: AppHandle) -> Result<Vec<UserTheme>, String> {
     Ok(themes)
 }
 
+/// Default and user themes in one list for the theme picker. Defaults keep their bare
+/// name; a user theme that replaced a default's file is listed as a separate "(Custom)" entry.
+#[command]
+pub async fn list_all_themes(app: AppHandle) -> Result<Vec<ThemeInfo>, String> {
+    let mut themes: Vec<ThemeInfo> = get_user_themes(app)
+        .await?
+        .into_iter()
+        .map(|theme| {
+            let stem = theme.name.strip_prefix("user-").unwrap_or(&theme.name);
+            if DEFAULT_THEME_NAMES.contains(&stem) && !theme.shadows_default {
+                ThemeInfo {
+                    name: stem.to_string(),
+                    display_name: theme.display_name,
+                    is_user_theme: false,
+                }
+            } else if theme.shadows_default {
+                ThemeInfo {
+                    display_name: format!("{} (Custom)", theme.display_name),
+                    name: theme.name,
+                    is_user_theme: true,
+                }
+            } else {
+                ThemeInfo {
+                    name: theme.name,
+                    display_name: theme.display_name,
+                    is_user_theme: true,
+                }
+            }
+        })
+        .collect();
+    
+    themes.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    
+    Ok(themes)
+}
+
+/// Get all user themes along with their parsed CSS variables
+#[command]
+pub async fn get_user_themes_parsed(app: AppHandle) -> Result<Vec<ParsedUserTheme>, String> {
+    let themes = get_user_themes(app).await?;
+    
+    Ok(themes
+        .into_iter()
+        .map(|theme| ParsedUserTheme {
+            variables: parse_css_variables(&theme.content),
+            name: theme.name,
+            display_name: theme.display_name,
+            content: theme.content,
+            file_path: theme.file_path,
+        })
+        .collect())
+}
+
 /// Save a theme to the user themes directory
 #[command]
 pub async fn save_user_theme(
@@ -97,6 +218,12 @@ pub async fn save_user_theme(
     name: String,
     content: String,
 ) -> Result<String, String> {
+    ensure_writable(&app)?;
+    let validation = check_theme_css(&content);
+    if !validation.valid {
+        return Err(format!("Invalid theme: {}", validation.errors.join("; ")));
+    }
+
     // Sanitize the name to be filesystem-safe
     let sanitized_name = sanitize_filename(&name);
     
@@ -121,9 +248,129 @@ pub async fn save_user_theme(
     Ok(theme_file.to_string_lossy().to_string())
 }
 
+/// Import a theme from a `.css` file elsewhere on disk, named after the file
+#[command]
+pub async fn import_user_theme(
+    app: AppHandle,
+    source_path: String,
+    overwrite: Option<bool>,
+) -> Result<UserTheme, String> {
+    ensure_writable(&app)?;
+    let source = std::path::Path::new(&source_path);
+    if source.extension().and_then(|s| s.to_str()) != Some("css") {
+        return Err(format!("Not a .css file: {}", source_path));
+    }
+    
+    let content = std::fs::read_to_string(source)
+        .map_err(|e| format!("Failed to read {}: {}", source_path, e))?;
+    
+    let validation = check_theme_css(&content);
+    if !validation.valid {
+        return Err(format!("Invalid theme: {}", validation.errors.join("; ")));
+    }
+    
+    let sanitized_name = sanitize_filename(
+        source.file_stem().and_then(|s| s.to_str()).unwrap_or_default()
+    );
+    if sanitized_name.is_empty() {
+        return Err(format!("Cannot derive a theme name from {}", source_path));
+    }
+    
+    let theme_file = ensure_themes_dir(&app)?.join(format!("{}.css", sanitized_name));
+    if theme_file.exists() && !overwrite.unwrap_or(false) {
+        return Err(format!("A theme named '{}' already exists", sanitized_name));
+    }
+    
+    std::fs::write(&theme_file, &content)
+        .map_err(|e| format!("Failed to write theme file: {}", e))?;
+    
+    Ok(user_theme(&sanitized_name, content, &theme_file))
+}
+
+/// Copy an existing theme (user or default) under a new name
+#[command]
+pub async fn duplicate_theme(
+    app: AppHandle,
+    source_name: String,
+    new_name: String,
+) -> Result<UserTheme, String> {
+    ensure_writable(&app)?;
+    // Defaults live in the same directory as user themes, so one lookup covers both
+    let content = read_theme_css(&app, &source_name)?;
+    
+    let sanitized_name = sanitize_filename(&new_name);
+    if sanitized_name.is_empty() {
+        return Err(format!("Invalid theme name: {}", new_name));
+    }
+    
+    let theme_file = ensure_themes_dir(&app)?.join(format!("{}.css", sanitized_name));
+    if theme_file.exists() {
+        return Err(format!("A theme named '{}' already exists", sanitized_name));
+    }
+    
+    std::fs::write(&theme_file, &content)
+        .map_err(|e| format!("Failed to write theme file: {}", e))?;
+    
+    Ok(user_theme(&sanitized_name, content, &theme_file))
+}
+
+/// Rename a user theme's file, keeping it active if it was the active theme
+#[command]
+pub async fn rename_user_theme(
+    app: AppHandle,
+    old_name: String,
+    new_name: String,
+) -> Result<UserTheme, String> {
+    ensure_writable(&app)?;
+    let old_stem = sanitize_filename(old_name.strip_prefix("user-").unwrap_or(&old_name));
+    let new_stem = sanitize_filename(&new_name);
+    if new_stem.is_empty() {
+        return Err(format!("Invalid theme name: {}", new_name));
+    }
+    // initialize_default_themes would write a renamed default back on the next start
+    if DEFAULT_THEME_NAMES.contains(&old_stem.as_str()) {
+        return Err(format!("Cannot rename the built-in theme '{}'", old_stem));
+    }
+    if DEFAULT_THEME_NAMES.contains(&new_stem.as_str()) {
+        return Err(format!("'{}' is reserved for a built-in theme", new_stem));
+    }
+    
+    let themes_dir = ensure_themes_dir(&app)?;
+    let old_file = themes_dir.join(format!("{}.css", old_stem));
+    let new_file = themes_dir.join(format!("{}.css", new_stem));
+    if !old_file.exists() {
+        return Err(format!("Theme not found: {}", old_name));
+    }
+    if new_stem != old_stem && new_file.exists() {
+        return Err(format!("A theme named '{}' already exists", new_stem));
+    }
+    
+    std::fs::rename(&old_file, &new_file)
+        .map_err(|e| format!("Failed to rename theme file: {}", e))?;
+    
+    let mut settings = read_settings(&app);
+    let old_active = format!("user-{}", old_stem);
+    if settings.active_theme.as_deref() == Some(old_active.as_str()) {
+        settings.active_theme = Some(format!("user-{}", new_stem));
+        write_settings(&app, &settings)?;
+    }
+    
+    let content = std::fs::read_to_string(&new_file)
+        .map_err(|e| format!("Failed to read theme file {}: {}", new_file.display(), e))?;
+    
+    Ok(user_theme(&new_stem, content, &new_file))
+}
+
+/// Check theme CSS without saving it, so the editor can highlight missing variables
+#[command]
+pub async fn validate_theme_css(content: String) -> Result<ThemeValidation, String> {
+    Ok(check_theme_css(&content))
+}
+
 /// Delete a user theme
 #[command]
 pub async fn delete_user_theme(app: AppHandle, theme_name: String) -> Result<(), String> {
+    ensure_writable(&app)?;
     // Remove "user-" prefix if present
     let clean_name = theme_name.strip_prefix("user-").unwrap_or(&theme_name);
     let sanitized_name = sanitize_filename(clean_name);
@@ -143,6 +390,89 @@ pub async fn delete_user_theme(app: AppHandle, theme_name: String) -> Result<(),
     Ok(())
 }
 
+/// Check whether a theme name is free before saving, reporting the sanitized file stem
+#[command]
+pub async fn is_theme_name_available(
+    app: AppHandle,
+    name: String,
+) -> Result<ThemeNameAvailability, String> {
+    let clean_name = name.strip_prefix("user-").unwrap_or(&name);
+    let sanitized_name = sanitize_filename(clean_name);
+    
+    if sanitized_name.is_empty() {
+        return Ok(ThemeNameAvailability {
+            available: false,
+            sanitized_name,
+        });
+    }
+    
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    
+    let theme_file = app_data_dir.join("themes").join(format!("{}.css", sanitized_name));
+    let is_default = DEFAULT_THEME_NAMES.contains(&sanitized_name.as_str());
+    
+    Ok(ThemeNameAvailability {
+        available: !is_default && !theme_file.exists(),
+        sanitized_name,
+    })
+}
+
+/// Get the persisted active theme name, if one has been chosen
+#[command]
+pub async fn get_active_theme(app: AppHandle) -> Result<Option<String>, String> {
+    Ok(read_settings(&app).active_theme)
+}
+
+/// Persist the active theme name
+#[command]
+pub async fn set_active_theme(app: AppHandle, theme_name: String) -> Result<(), String> {
+    let mut settings = read_settings(&app);
+    settings.active_theme = Some(theme_name);
+    write_settings(&app, &settings)
+}
+
+/// Switch the active theme to its light/dark counterpart
+#[command]
+pub async fn toggle_theme_variant(app: AppHandle) -> Result<ThemeVariantToggle, String> {
+    let mut settings = read_settings(&app);
+    let current = settings
+        .active_theme
+        .clone()
+        .ok_or_else(|| "No active theme is set".to_string())?;
+    
+    // User copies of the defaults carry a "user-" prefix; keep it on the counterpart
+    let (prefix, base) = match current.strip_prefix("user-") {
+        Some(base) => ("user-", base),
+        None => ("", current.as_str()),
+    };
+    
+    let paired = THEME_VARIANT_PAIRS.iter().find_map(|(light, dark)| {
+        if *light == base {
+            Some(*dark)
+        } else if *dark == base {
+            Some(*light)
+        } else {
+            None
+        }
+    });
+    
+    match paired {
+        Some(paired) => {
+            let theme = format!("{}{}", prefix, paired);
+            settings.active_theme = Some(theme.clone());
+            write_settings(&app, &settings)?;
+            Ok(ThemeVariantToggle { theme, toggled: true })
+        }
+        None => Ok(ThemeVariantToggle {
+            theme: current,
+            toggled: false,
+        }),
+    }
+}
+
 /// Check if themes directory exists and is accessible
 #[command]
 pub async fn check_themes_directory(app: AppHandle) -> Result<bool, String> {
@@ -205,6 +535,52 @@ pub async fn initialize_default_themes(app: AppHandle) -> Result<(), String> {
             .map_err(|e| format!("Failed to create themes directory: {}", e))?;
     }
 
+    for (filename, content) in default_theme_files() {
+        let theme_file = themes_dir.join(filename);
+        if !theme_file.exists() {
+            std::fs::write(&theme_file, content)
+                .map_err(|e| format!("Failed to write {} theme file: {}", filename, e))?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Watch the themes directory and emit `themes-changed` when theme files are edited on disk.
+/// Changes are debounced so one save in an editor produces one event.
+pub fn watch_themes_directory(app: &AppHandle) -> Result<(), String> {
+    let themes_dir = ensure_themes_dir(app)?;
+    
+    let handle = app.clone();
+    let mut debouncer = new_debouncer(Duration::from_millis(500), move |result: DebounceEventResult| {
+        let Ok(events) = result else {
+            return;
+        };
+        let css_changed = events
+            .iter()
+            .any(|event| event.path.extension().and_then(|s| s.to_str()) == Some("css"));
+        if css_changed {
+            let _ = handle.emit("themes-changed", ());
+        }
+    })
+    .map_err(|e| format!("Failed to create themes watcher: {}", e))?;
+    
+    debouncer
+        .watcher()
+        .watch(&themes_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch themes directory: {}", e))?;
+    
+    app.manage(ThemesWatcher {
+        _debouncer: Mutex::new(debouncer),
+    });
+    
+    Ok(())
+}
+
+// Helper functions
+
+/// File names and CSS of the themes written by `initialize_default_themes`
+fn default_theme_files() -> Vec<(&'static str, &'static str)> {
     let vivid_color_css = r#":root {
         --color-primary: #0801ff !important;
         --color-background-primary: #111827 !important;
@@ -326,8 +702,7 @@ pub async fn initialize_default_themes(app: AppHandle) -> Result<(), String> {
         --color-danger: #d11c18 !important;
     }"#;
 
-    // Create all additional theme files
-    let theme_files = vec![
+    vec![
         ("vivid-night.css", vivid_color_css),
         ("purple-haze.css", purple_haze_css),
         ("seaside-picnic.css", seaside_picnic_css),
@@ -339,20 +714,149 @@ pub async fn initialize_default_themes(app: AppHandle) -> Result<(), String> {
         ("cloudy-thoughts.css", cloudy_thoughts_css),
         ("blackberry-fizz.css", blackberry_fizz_css),
         ("bleeding-heart.css", bleeding_heart_css),
-    ];
+    ]
+}
 
-    for (filename, content) in theme_files {
-        let theme_file = themes_dir.join(filename);
-        if !theme_file.exists() {
-            std::fs::write(&theme_file, content)
-                .map_err(|e| format!("Failed to write {} theme file: {}", filename, e))?;
-        }
+/// The themes directory in app data, created if missing
+fn ensure_themes_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let themes_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?
+        .join("themes");
+    
+    if !themes_dir.exists() {
+        std::fs::create_dir_all(&themes_dir)
+            .map_err(|e| format!("Failed to create themes directory: {}", e))?;
     }
     
-    Ok(())
+    Ok(themes_dir)
 }
 
-// Helper functions
+/// Build the `UserTheme` that `get_user_themes` would list for a theme file
+fn user_theme(stem: &str, content: String, path: &std::path::Path) -> UserTheme {
+    UserTheme {
+        name: format!("user-{}", stem),
+        display_name: capitalize_words(stem),
+        shadows_default: shadows_default(stem, &content),
+        content,
+        file_path: path.to_string_lossy().to_string(),
+    }
+}
+
+/// Whether a theme file named like a default has been replaced with different CSS
+fn shadows_default(stem: &str, content: &str) -> bool {
+    default_theme_files().iter().any(|(filename, css)| {
+        filename.strip_suffix(".css") == Some(stem) && css.trim() != content.trim()
+    })
+}
+
+/// Read the CSS for a theme by name, accepting the `user-` prefix used by `get_user_themes`
+pub(crate) fn read_theme_css(app: &AppHandle, theme_name: &str) -> Result<String, String> {
+    let clean_name = theme_name.strip_prefix("user-").unwrap_or(theme_name);
+    let sanitized_name = sanitize_filename(clean_name);
+    
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    
+    let theme_file = app_data_dir.join("themes").join(format!("{}.css", sanitized_name));
+    
+    if !theme_file.exists() {
+        return Err(format!("Theme not found: {}", theme_name));
+    }
+    
+    std::fs::read_to_string(&theme_file)
+        .map_err(|e| format!("Failed to read theme file {}: {}", theme_file.display(), e))
+}
+
+/// Validate that theme CSS is a single `:root { ... }` block defining the required variables,
+/// with no `@import` or `url(` that could pull in remote resources
+fn check_theme_css(content: &str) -> ThemeValidation {
+    let content = strip_css_comments(content);
+    let mut errors = Vec::new();
+
+    let lower = content.to_lowercase();
+    if lower.contains("@import") {
+        errors.push("@import is not allowed".to_string());
+    }
+    if lower.contains("url(") {
+        errors.push("url() is not allowed".to_string());
+    }
+
+    let variables = match root_block(&content) {
+        Some(body) => parse_css_variables(body),
+        None => {
+            errors.push("Theme must be a single :root { ... } block".to_string());
+            HashMap::new()
+        }
+    };
+
+    let missing_variables: Vec<String> = REQUIRED_THEME_VARIABLES
+        .iter()
+        .filter(|name| !variables.contains_key(**name))
+        .map(|name| name.to_string())
+        .collect();
+    if !missing_variables.is_empty() {
+        errors.push(format!("Missing variables: {}", missing_variables.join(", ")));
+    }
+
+    ThemeValidation {
+        valid: errors.is_empty(),
+        missing_variables,
+        errors,
+    }
+}
+
+/// Body of a stylesheet consisting of exactly one `:root { ... }` rule
+fn root_block(content: &str) -> Option<&str> {
+    let body = content
+        .trim()
+        .strip_prefix(":root")?
+        .trim_start()
+        .strip_prefix('{')?
+        .strip_suffix('}')?;
+    // Any other brace means nested or additional rules
+    if body.contains('{') || body.contains('}') {
+        return None;
+    }
+    Some(body)
+}
+
+/// Custom property declarations (`--name: value`) anywhere in the CSS, with `!important` dropped
+fn parse_css_variables(content: &str) -> HashMap<String, String> {
+    strip_css_comments(content)
+        .split(|c: char| c == ';' || c == '{' || c == '}')
+        .filter_map(|declaration| {
+            let (name, value) = declaration.split_once(':')?;
+            let name = name.trim();
+            if !name.starts_with("--") {
+                return None;
+            }
+            let value = value.trim();
+            let value = value.strip_suffix("!important").unwrap_or(value).trim();
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn strip_css_comments(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        match rest[start + 2..].find("*/") {
+            Some(end) => rest = &rest[start + 2 + end + 2..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
 
 fn capitalize_words(s: &str) -> String {
     s.split('-')
@@ -367,7 +871,7 @@ fn capitalize_words(s: &str) -> String {
         .join(" ")
 }
 
-fn sanitize_filename(name: &str) -> String {
+pub(crate) fn sanitize_filename(name: &str) -> String {
     // Remove or replace invalid characters for filenames
     name.chars()
         .map(|c| match c {
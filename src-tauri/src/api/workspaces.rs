@@ -0,0 +1,202 @@
+// src-tauri/src/api/workspaces.rs
+
+use crate::api::settings::read_settings;
+use crate::api::themes::sanitize_filename;
+use crate::core::db::{open_database, DbState};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::{fs, path::PathBuf};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+
+/// Name reported for the original `tree.db` database
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+// Resolved once per run, so a new `database_path` setting only applies after a restart
+static DEFAULT_DB_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkspaceInfo {
+    pub name: String,
+    pub path: String,
+    pub active: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct WorkspaceStateFile {
+    active: Option<String>,
+}
+
+fn get_app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))
+}
+
+fn get_workspaces_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let workspaces_dir = get_app_data_dir(app)?.join("workspaces");
+    if !workspaces_dir.exists() {
+        fs::create_dir_all(&workspaces_dir)
+            .map_err(|e| format!("Failed to create workspaces directory: {}", e))?;
+    }
+    Ok(workspaces_dir)
+}
+
+fn get_state_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(get_app_data_dir(app)?.join("workspace_state.json"))
+}
+
+fn read_state(app: &AppHandle) -> WorkspaceStateFile {
+    let path = match get_state_file_path(app) {
+        Ok(p) => p,
+        Err(_) => return WorkspaceStateFile::default(),
+    };
+    if !path.exists() {
+        return WorkspaceStateFile::default();
+    }
+    let data = fs::read_to_string(path).unwrap_or_default();
+    serde_json::from_str(&data).unwrap_or_default()
+}
+
+fn write_state(app: &AppHandle, state: &WorkspaceStateFile) -> Result<(), String> {
+    let path = get_state_file_path(app)?;
+    let data = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    fs::write(path, data).map_err(|e| format!("Failed to write workspace state: {}", e))
+}
+
+fn validate_workspace_name(name: &str) -> Result<String, String> {
+    let sanitized = sanitize_filename(name);
+    if sanitized.is_empty() {
+        return Err(format!("Invalid workspace name: {}", name));
+    }
+    Ok(sanitized)
+}
+
+fn workspace_db_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    if name == DEFAULT_WORKSPACE {
+        if let Some(path) = DEFAULT_DB_PATH.get() {
+            return Ok(path.clone());
+        }
+        let path = match read_settings(app).database_path {
+            Some(path) => PathBuf::from(path),
+            None => get_app_data_dir(app)?.join("tree.db"),
+        };
+        Ok(DEFAULT_DB_PATH.get_or_init(|| path).clone())
+    } else {
+        Ok(get_workspaces_dir(app)?.join(format!("{}.db", name)))
+    }
+}
+
+/// Name of the workspace that should be opened, falling back to the default database
+pub fn active_workspace(app: &AppHandle) -> String {
+    read_state(app)
+        .active
+        .filter(|name| {
+            workspace_db_path(app, name)
+                .map(|path| path.exists())
+                .unwrap_or(false)
+        })
+        .unwrap_or_else(|| DEFAULT_WORKSPACE.to_string())
+}
+
+/// Database file for the active workspace
+pub fn active_database_path(app: &AppHandle) -> Result<PathBuf, String> {
+    workspace_db_path(app, &active_workspace(app))
+}
+
+#[command]
+pub async fn list_workspaces(app: AppHandle) -> Result<Vec<WorkspaceInfo>, String> {
+    let active = active_workspace(&app);
+    let mut workspaces = vec![WorkspaceInfo {
+        name: DEFAULT_WORKSPACE.to_string(),
+        path: workspace_db_path(&app, DEFAULT_WORKSPACE)?.to_string_lossy().to_string(),
+        active: active == DEFAULT_WORKSPACE,
+    }];
+
+    let mut named = Vec::new();
+    let entries = fs::read_dir(get_workspaces_dir(&app)?)
+        .map_err(|e| format!("Failed to read workspaces directory: {}", e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("db") {
+            let name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string();
+            named.push(WorkspaceInfo {
+                active: active == name,
+                name,
+                path: path.to_string_lossy().to_string(),
+            });
+        }
+    }
+
+    named.sort_by(|a, b| a.name.cmp(&b.name));
+    workspaces.extend(named);
+    Ok(workspaces)
+}
+
+#[command]
+pub async fn create_workspace(app: AppHandle, name: String, db: State<'_, DbState>) -> Result<WorkspaceInfo, String> {
+    let name = validate_workspace_name(&name)?;
+    if name == DEFAULT_WORKSPACE {
+        return Err(format!("'{}' is reserved for the default workspace", DEFAULT_WORKSPACE));
+    }
+
+    let path = workspace_db_path(&app, &name)?;
+    if path.exists() {
+        return Err(format!("Workspace '{}' already exists", name));
+    }
+
+    // Opening creates the file and applies the schema; the pool is not needed afterwards.
+    // While the database is encrypted, new workspaces are created with the same passphrase.
+    let pool = open_database(&path, db.key().as_deref())
+        .await
+        .map_err(|e| format!("Failed to create workspace database: {}", e))?;
+    pool.close().await;
+
+    Ok(WorkspaceInfo {
+        name,
+        path: path.to_string_lossy().to_string(),
+        active: false,
+    })
+}
+
+#[command]
+pub async fn switch_workspace(
+    app: AppHandle,
+    name: String,
+    db: State<'_, DbState>,
+) -> Result<(), String> {
+    let name = if name == DEFAULT_WORKSPACE {
+        name
+    } else {
+        validate_workspace_name(&name)?
+    };
+
+    let path = workspace_db_path(&app, &name)?;
+    if !path.exists() {
+        return Err(format!("Workspace '{}' does not exist", name));
+    }
+
+    let pool = open_database(&path, db.key().as_deref())
+        .await
+        .map_err(|e| format!("Failed to open workspace database: {}", e))?;
+    let previous = db.replace(pool);
+    previous.close().await;
+
+    write_state(
+        &app,
+        &WorkspaceStateFile {
+            active: Some(name.clone()),
+        },
+    )?;
+
+    // The switch is already done, so a failed emit is only logged rather than failing the command
+    if let Err(e) = app.emit("workspace-changed", &name) {
+        eprintln!("Failed to emit workspace change: {}", e);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+// src-tauri/src/core/db.rs
+
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode};
+use sqlx::{Pool, Sqlite};
+use std::io::Read;
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Managed database handle whose pool can be swapped when the active workspace changes
+pub struct DbState {
+    pool: RwLock<Pool<Sqlite>>,
+    // SQLCipher passphrase the database was unlocked with, reused whenever another database is opened
+    key: RwLock<Option<String>>,
+}
+
+impl DbState {
+    pub fn new(pool: Pool<Sqlite>, key: Option<String>) -> Self {
+        Self {
+            pool: RwLock::new(pool),
+            key: RwLock::new(key),
+        }
+    }
+
+    /// Clone out the current pool (cheap, pools are reference counted)
+    pub fn pool(&self) -> Pool<Sqlite> {
+        self.pool.read().expect("database lock poisoned").clone()
+    }
+
+    /// Install a new pool, returning the previous one so the caller can close it
+    pub fn replace(&self, pool: Pool<Sqlite>) -> Pool<Sqlite> {
+        std::mem::replace(&mut *self.pool.write().expect("database lock poisoned"), pool)
+    }
+
+    pub fn key(&self) -> Option<String> {
+        self.key.read().expect("database lock poisoned").clone()
+    }
+
+    pub fn set_key(&self, key: Option<String>) {
+        *self.key.write().expect("database lock poisoned") = key;
+    }
+}
+
+/// Whether `path` is an unencrypted SQLite file; SQLCipher databases don't start with the plain header
+pub fn is_plaintext_database(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map(|_| &header == b"SQLite format 3\0")
+        .unwrap_or(false)
+}
+
+/// Open (creating if missing) the SQLite database at `path` and bring its schema up to date.
+///
+/// Connections use WAL so reads aren't blocked by a writer, wait up to five seconds on a locked
+/// database, and enforce foreign keys: deleting a note or a tag removes its `note_tags` rows
+/// through `ON DELETE CASCADE`, while the tag itself is left for `cleanup_orphaned_tags`.
+///
+/// With a `key`, the file is opened (or created) as a SQLCipher database. Existing plaintext files
+/// are opened without it, so unencrypted workspaces stay readable after encryption is turned on.
+pub async fn open_database(path: &Path, key: Option<&str>) -> Result<Pool<Sqlite>, sqlx::Error> {
+    let mut options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .busy_timeout(Duration::from_secs(5))
+        .foreign_keys(true);
+    // sqlx always issues `key` before any other pragma, as SQLCipher requires
+    if let Some(key) = key.filter(|_| !is_plaintext_database(path)) {
+        options = options.pragma("key", quote_sql_string(key));
+    }
+
+    let pool = Pool::<Sqlite>::connect_with(options).await?;
+
+    run_migrations(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Apply pending migrations from `migrations/`, each exactly once
+pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::migrate!("./migrations").run(pool).await?;
+    Ok(())
+}
+
+// Pragma values can't be bound, so quote the passphrase as a SQL string literal
+fn quote_sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
@@ -0,0 +1,190 @@
+// src-tauri/src/core/markdown.rs
+
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+/// Markdown extensions the renderer can enable, persisted as the `markdown_options` setting
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MarkdownOptions {
+    pub tables: bool,
+    pub task_lists: bool,
+    pub strikethrough: bool,
+    pub highlight: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            task_lists: true,
+            strikethrough: true,
+            highlight: true,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    fn parser_options(&self) -> Options {
+        let mut options = Options::empty();
+        options.set(Options::ENABLE_TABLES, self.tables);
+        options.set(Options::ENABLE_TASKLISTS, self.task_lists);
+        options.set(Options::ENABLE_STRIKETHROUGH, self.strikethrough);
+        options
+    }
+}
+
+/// Render markdown to an HTML fragment, optionally stripping unsafe markup
+pub fn render_html(content: &str, sanitize: bool, options: &MarkdownOptions) -> String {
+    render_html_with_note_links(content, sanitize, options, |_| None)
+}
+
+/// Like `render_html`, pointing each `note:<id>` link at `note_href(id)`. Links it returns
+/// `None` for are left as they are, and lose their href when sanitized.
+pub fn render_html_with_note_links(
+    content: &str,
+    sanitize: bool,
+    options: &MarkdownOptions,
+    note_href: impl Fn(i64) -> Option<String>,
+) -> String {
+    let parser = Parser::new_ext(content, options.parser_options()).map(|event| match event {
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
+            let dest_url = match note_link_target(&dest_url).and_then(&note_href) {
+                Some(href) => CowStr::from(href),
+                None => dest_url,
+            };
+            Event::Start(Tag::Link { link_type, dest_url, title, id })
+        }
+        other => other,
+    });
+    let mut output = String::new();
+
+    if options.highlight {
+        // pulldown-cmark has no ==highlight== syntax, so rewrite it in text runs outside code blocks
+        let mut in_code_block = false;
+        let events = parser.flat_map(move |event| match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                in_code_block = true;
+                vec![event]
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                vec![event]
+            }
+            Event::Text(text) if !in_code_block && text.contains("==") => highlight_events(&text),
+            other => vec![other],
+        });
+        html::push_html(&mut output, events);
+    } else {
+        html::push_html(&mut output, parser);
+    }
+
+    if sanitize {
+        // Keep the disabled checkboxes emitted for task lists
+        ammonia::Builder::default()
+            .add_tags(["input"])
+            .add_tag_attributes("input", ["type", "checked", "disabled"])
+            .clean(&output)
+            .to_string()
+    } else {
+        output
+    }
+}
+
+/// Ids of the notes `content` links to, in order of first appearance. Links between notes are
+/// ordinary markdown links to `note:<id>`, e.g. `[see also](note:42)`.
+pub fn linked_note_ids(content: &str) -> Vec<i64> {
+    let mut ids = Vec::new();
+    for event in Parser::new(content) {
+        if let Event::Start(Tag::Link { dest_url, .. }) = event {
+            if let Some(id) = note_link_target(&dest_url) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+    }
+    ids
+}
+
+/// The note id a link destination points at, when it is a `note:<id>` link
+pub fn note_link_target(dest_url: &str) -> Option<i64> {
+    dest_url.strip_prefix("note:")?.parse().ok()
+}
+
+// Split a text run on matched `==` pairs, wrapping the inner text in <mark>
+fn highlight_events(text: &str) -> Vec<Event<'static>> {
+    let mut events = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("==") {
+        let after = &rest[start + 2..];
+        match after.find("==") {
+            Some(end) if end > 0 => {
+                events.push(Event::Text(CowStr::from(rest[..start].to_string())));
+                events.push(Event::InlineHtml(CowStr::Borrowed("<mark>")));
+                events.push(Event::Text(CowStr::from(after[..end].to_string())));
+                events.push(Event::InlineHtml(CowStr::Borrowed("</mark>")));
+                rest = &after[end + 2..];
+            }
+            _ => break,
+        }
+    }
+
+    events.push(Event::Text(CowStr::from(rest.to_string())));
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{linked_note_ids, render_html, render_html_with_note_links, MarkdownOptions};
+
+    #[test]
+    fn linked_note_ids_reads_note_links_only() {
+        let content = "See [one](note:1), [web](https://example.com) and [again](note:1).\n\n[two](note:2)";
+        assert_eq!(linked_note_ids(content), vec![1, 2]);
+        assert!(linked_note_ids("[bad](note:abc) `[code](note:3)`").is_empty());
+    }
+
+    #[test]
+    fn highlight_skips_code_blocks() {
+        let options = MarkdownOptions::default();
+        assert!(render_html("a ==marked== word", false, &options).contains("<mark>marked</mark>"));
+
+        let fenced = render_html("```\nif a == b && c == d {}\n```", false, &options);
+        assert!(!fenced.contains("<mark>"));
+        assert!(fenced.contains("if a == b &amp;&amp; c == d {}"));
+
+        let indented = render_html("    x == 1 || y == 2", false, &options);
+        assert!(!indented.contains("<mark>"));
+    }
+
+    #[test]
+    fn tables_follow_options() {
+        let table = "| a | b |\n|---|---|\n| 1 | 2 |";
+        let on = MarkdownOptions::default();
+        let off = MarkdownOptions { tables: false, ..MarkdownOptions::default() };
+        assert!(render_html(table, false, &on).contains("<table>"));
+        assert!(!render_html(table, false, &off).contains("<table>"));
+    }
+
+    #[test]
+    fn task_lists_follow_options() {
+        let tasks = "- [x] done\n- [ ] todo";
+        let on = MarkdownOptions::default();
+        let off = MarkdownOptions { task_lists: false, ..MarkdownOptions::default() };
+        assert!(render_html(tasks, true, &on).contains("type=\"checkbox\""));
+        let plain = render_html(tasks, true, &off);
+        assert!(!plain.contains("<input"));
+        assert!(plain.contains("[x] done"));
+    }
+
+    #[test]
+    fn note_links_resolve_to_hrefs() {
+        let content = "[kept](note:1) and [missing](note:2)";
+        let href = |id: i64| (id == 1).then(|| format!("note-{}.html", id));
+        let html = render_html_with_note_links(content, true, &MarkdownOptions::default(), href);
+        assert!(html.contains("href=\"note-1.html\""));
+        assert!(!html.contains("note:2"));
+    }
+}
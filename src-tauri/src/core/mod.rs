@@ -1 +1,4 @@
 pub mod models;
+pub mod db;
+pub mod markdown;
+pub mod similarity;
@@ -6,28 +6,229 @@ use sqlx::FromRow; // Import the FromRow trait
 #[derive(Debug, Serialize, Deserialize, Clone, FromRow)] // Add FromRow here
 pub struct Note {
     pub id: i64,
+    pub title: String,
     pub content: String,
-    pub created_at: Option<String>,
-    pub updated_at: Option<String>,
+    // Unix epoch milliseconds
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub is_pinned: bool,
+    pub is_favorite: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct NoteRevision {
+    pub id: i64,
+    pub note_id: i64,
+    pub title: String,
+    pub content: String,
+    // Unix epoch milliseconds
+    pub saved_at: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, FromRow)] // And also here for later
 pub struct Tag {
     pub id: i64,
     pub name: String,
+    // Hex color like "#ff6766"; missing in tag files exported before colors existed
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct TagWithCount {
+    pub id: i64,
+    pub name: String,
+    pub note_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct TagInsight {
+    pub id: i64,
+    pub name: String,
+    pub count: i64,
+    pub last_used: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct TagStorage {
+    pub tag: String,
+    pub total_chars: i64,
+    pub note_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RepairReport {
+    pub dangling_note_links: u64,
+    pub dangling_tag_links: u64,
+    pub orphaned_tags: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupImportCounts {
+    pub notes_imported: u64,
+    pub tags_imported: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// Payload of the `notes-changed` event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotesChanged {
+    pub id: i64,
+    pub kind: NoteChangeKind,
+}
+
+/// Payload of the `note-save-failed` event, sent when a debounced update couldn't be written
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoteSaveFailed {
+    pub id: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct SidebarCounts {
+    pub total: i64,
+    pub archived: i64,
+    pub favorites: i64,
+    pub trashed: i64,
+    pub untagged: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct NoteLength {
+    pub id: i64,
+    pub length: i64,
+}
+
+/// List-view form of a note: the full content is replaced by a short plain-text preview
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotePreview {
+    pub id: i64,
+    pub title: String,
+    pub preview: String,
+    // Unix epoch milliseconds
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub is_pinned: bool,
+    pub is_favorite: bool,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoteStats {
+    pub words: u64,
+    // Unicode scalar values: "é" or "😀" counts once, but combining marks and emoji modifiers count separately
+    pub characters: u64,
+    pub lines: u64,
+    pub reading_time_minutes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LengthExtremes {
+    pub longest: Vec<NoteLength>,
+    pub shortest: Vec<NoteLength>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StreakInfo {
+    pub current_streak: i64,
+    pub longest_streak: i64,
+    pub last_write: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoteChunk {
+    pub text: String,
+    pub total_len: i64,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimilarPair {
+    pub a: i64,
+    pub b: i64,
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct Snippet {
+    pub id: i64,
+    pub trigger: String,
+    pub content: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NoteWithTags {
     pub id: i64,
+    pub title: String,
     pub content: String,
-    pub created_at: Option<String>,
-    pub updated_at: Option<String>,
+    // Unix epoch milliseconds
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub is_pinned: bool,
+    // Missing in backups written before favorites existed
+    #[serde(default)]
+    pub is_favorite: bool,
     pub tags: Vec<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatchMode {
+    #[default]
+    All,
+    Any,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteSortOrder {
+    #[default]
+    CreatedDesc,
+    CreatedAsc,
+    UpdatedDesc,
+    UpdatedAsc,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotesPage {
+    pub notes: Vec<NoteWithTags>,
+    pub total: i64,
+}
+
+/// Byte range of a search match within a note's content
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MatchSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A text search result; serializes exactly like NoteWithTags unless match offsets were requested
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TextSearchHit {
+    #[serde(flatten)]
+    pub note: NoteWithTags,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matches: Option<Vec<MatchSpan>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchPage {
+    pub results: Vec<NoteWithTags>,
+    pub total: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CreateNoteRequest {
+    // Derived from the first line of content when missing
+    #[serde(default)]
+    pub title: Option<String>,
     pub content: String,
     pub tags: Vec<String>,
 }
@@ -35,6 +236,8 @@ pub struct CreateNoteRequest {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateNoteRequest {
     pub id: i64,
+    #[serde(default)]
+    pub title: Option<String>,
     pub content: String,
     pub tags: Vec<String>,
 }
\ No newline at end of file
@@ -0,0 +1,88 @@
+// src-tauri/src/core/similarity.rs
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+// Words per shingle
+const SHINGLE_SIZE: usize = 3;
+// MinHash signature is split into BANDS bands of ROWS values; notes sharing any band become candidates.
+// 16 bands of 4 rows puts the candidate cutoff near a Jaccard score of 0.5.
+const BANDS: usize = 16;
+const ROWS: usize = 4;
+
+/// Hashed word shingles for a piece of text
+pub fn shingles(text: &str) -> HashSet<u64> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+
+    if words.is_empty() {
+        return HashSet::new();
+    }
+    if words.len() < SHINGLE_SIZE {
+        return HashSet::from([hash_value(&words)]);
+    }
+
+    words.windows(SHINGLE_SIZE).map(hash_value).collect()
+}
+
+pub fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Pairs of indexes into `sets` worth comparing exactly, found by MinHash banding instead of all O(n²) pairs
+pub fn candidate_pairs(sets: &[HashSet<u64>]) -> HashSet<(usize, usize)> {
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+
+    for (index, set) in sets.iter().enumerate() {
+        if set.is_empty() {
+            continue;
+        }
+        let signature = min_hash(set);
+        for (band, rows) in signature.chunks(ROWS).enumerate() {
+            buckets.entry((band, hash_value(rows))).or_default().push(index);
+        }
+    }
+
+    let mut pairs = HashSet::new();
+    for members in buckets.values() {
+        for (i, a) in members.iter().enumerate() {
+            for b in &members[i + 1..] {
+                pairs.insert((*a.min(b), *a.max(b)));
+            }
+        }
+    }
+    pairs
+}
+
+fn min_hash(set: &HashSet<u64>) -> Vec<u64> {
+    (0..BANDS * ROWS)
+        .map(|seed| {
+            set.iter()
+                .map(|value| mix(*value, seed as u64))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+// Cheap independent hash family: reseed each value with a splitmix64 round
+fn mix(value: u64, seed: u64) -> u64 {
+    let mut z = value ^ seed.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn hash_value<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
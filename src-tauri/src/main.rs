@@ -5,8 +5,11 @@
  * Sets up Tauri application with database initialization and command handlers
  */
 
-use sqlx::SqlitePool;
-use tauri::Manager;
+use std::collections::HashSet;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+use crate::core::db::{open_database, DbState};
 
 mod core;
 mod api;
@@ -19,10 +22,27 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_opener::init())
         .setup(|app| {
+            // Debounced note writes waiting for their window to pass
+            app.manage(api::notes::PendingUpdates::default());
+            
+            // Let the frontend reload themes edited directly in the themes folder
+            if let Err(e) = api::themes::watch_themes_directory(app.handle()) {
+                eprintln!("{}", e);
+            }
+            
             // Initialize database in async context
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                setup_database(app_handle).await;
+                // Keep the window open on failure so the frontend can show what went wrong
+                if let Err(e) = setup_database(app_handle.clone()).await {
+                    eprintln!("Database setup failed: {}", e);
+                    app_handle.manage(api::database::DatabaseSetupError(e.clone()));
+                    if let Err(e) = app_handle.emit("database-error", &e) {
+                        eprintln!("Failed to emit database error: {}", e);
+                    }
+                    return;
+                }
+                poll_reminders(app_handle).await;
             });
             
             Ok(())
@@ -31,74 +51,246 @@ fn main() {
             // Note management commands
             api::notes::create_note,
             api::notes::get_all_notes,
+            api::notes::get_notes_paginated,
+            api::notes::get_note_previews,
+            api::notes::get_note_count,
             api::notes::get_note_by_id,
+            api::notes::duplicate_note,
             api::notes::update_note,
+            api::notes::update_note_debounced,
+            api::notes::get_note_revisions,
+            api::notes::search_revisions,
+            api::notes::restore_note_revision,
             api::notes::delete_note,
+            api::notes::delete_notes,
+            api::notes::restore_note,
+            api::notes::list_trashed_notes,
+            api::notes::purge_note,
+            api::notes::set_note_pinned,
+            api::notes::set_note_favorite,
+            api::notes::get_favorite_notes,
+            api::notes::archive_note,
+            api::notes::unarchive_note,
+            api::notes::get_archived_notes,
+            api::notes::get_orphan_notes,
             api::notes::get_tags_for_note,
+            api::notes::add_tag_to_notes,
+            api::notes::remove_tag_from_notes,
             api::notes::search_notes_by_tags,
+            api::notes::search_notes_by_text,
+            api::notes::search_notes,
+            api::notes::set_note_order,
+            api::notes::get_all_notes_ordered_manual,
+            api::notes::renumber_manual_order,
+            api::notes::get_sidebar_counts,
+            api::notes::find_note_by_content,
+            api::notes::get_note_length_extremes,
+            api::notes::split_note_by_headings,
+            api::notes::merge_notes,
+            api::notes::get_writing_streak,
+            api::notes::get_note_chunk,
+            api::notes::get_note_stats,
+            api::notes::set_note_reminder,
+            api::notes::clear_note_reminder,
+            api::notes::get_due_reminders,
+            api::notes::find_similar_notes,
+            api::notes::create_tag_checklist_note,
+            api::notes::search_scoped,
             // Tag management commands
             api::tags::get_all_tags,
+            api::tags::get_tags_with_counts,
+            api::tags::suggest_tags,
+            api::tags::get_tag_insights,
+            api::tags::get_storage_by_tag,
             api::tags::delete_tag,
             api::tags::rename_tag,
+            api::tags::set_tag_color,
+            api::tags::rename_tags_by_prefix,
+            api::tags::merge_tags,
+            api::tags::repair_associations,
+            // Storage commands
+            api::storage::get_storage_breakdown,
+            // Snippet commands
+            api::snippets::create_snippet,
+            api::snippets::list_snippets,
+            api::snippets::delete_snippet,
+            api::snippets::expand_snippet,
             // Theme management commands
             api::themes::get_themes_directory,
             api::themes::get_user_themes,
+            api::themes::get_user_themes_parsed,
+            api::themes::list_all_themes,
             api::themes::save_user_theme,
+            api::themes::import_user_theme,
+            api::themes::duplicate_theme,
+            api::themes::rename_user_theme,
+            api::themes::validate_theme_css,
             api::themes::delete_user_theme,
+            api::themes::is_theme_name_available,
+            api::themes::get_active_theme,
+            api::themes::set_active_theme,
+            api::themes::toggle_theme_variant,
             api::themes::check_themes_directory,
             api::themes::open_themes_directory,
             api::themes::initialize_default_themes,
             // Export functionality
             api::export::save_export_pdf,
+            api::export::export_note_gist,
+            api::export::export_note_html,
+            api::export::export_note_markdown,
+            api::export::export_database_file,
+            api::export::export_all_ndjson,
+            api::export::export_all_notes,
+            api::export::export_static_site,
+            api::export::export_tags_json,
+            api::export::import_tags_json,
+            api::export::export_backup_json,
+            api::export::import_backup_json,
+            api::export::prune_exports,
+            // Import commands
+            api::import::find_encoding_issues,
+            api::import::reimport_note,
+            api::import::import_bookmarks_html,
+            // Rendering commands
+            api::render::render_markdown_preview,
+            api::settings::get_markdown_options,
+            api::settings::set_markdown_options,
+            // Settings commands
+            api::settings::get_settings,
+            api::settings::update_settings,
+            api::settings::is_read_only,
+            api::settings::set_read_only,
             // Plugin management commands
             api::plugins::get_plugins_directory,
             api::plugins::open_plugins_directory,
             api::plugins::list_plugins,
-            api::plugins::set_plugin_enabled
+            api::plugins::install_plugin,
+            api::plugins::uninstall_plugin,
+            api::plugins::set_plugin_enabled,
+            api::plugins::check_plugin_compatibility,
+            api::plugins::get_plugin_assets,
+            api::plugins::get_plugin_config,
+            api::plugins::set_plugin_config,
+            // Workspace management commands
+            api::workspaces::list_workspaces,
+            api::workspaces::create_workspace,
+            api::workspaces::switch_workspace,
+            // Database maintenance commands
+            api::database::backup_database,
+            api::database::restore_database,
+            api::database::vacuum_database,
+            api::database::get_database_stats,
+            api::database::is_database_locked,
+            api::database::get_database_error,
+            api::database::unlock_database,
+            api::database::set_database_passphrase,
+            api::database::set_database_location
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                shutdown_database(app_handle);
+            }
+        });
 }
 
 /**
- * Initialize SQLite database with app data directory and migrations
+ * Initialize SQLite database for the active workspace and run migrations
  */
-async fn setup_database(app_handle: tauri::AppHandle) {
+async fn setup_database(app_handle: tauri::AppHandle) -> Result<(), String> {
     // Get app data directory and create database path
     let app_data_dir = app_handle
         .path()
         .app_data_dir()
-        .expect("Failed to get app data directory");
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
     
     // Create app data directory if it doesn't exist
     if !app_data_dir.exists() {
         std::fs::create_dir_all(&app_data_dir)
-            .expect("Failed to create app data directory");
+            .map_err(|e| format!("Failed to create app data directory: {}", e))?;
     }
     
-    let db_path = app_data_dir.join("tree.db");
-    let db_url = format!("sqlite:{}", db_path.to_string_lossy());
+    let db_path = api::workspaces::active_database_path(&app_handle)?;
     
-    println!("Database path: {}", db_url);
+    println!("Database path: {}", db_path.display());
     
-    // Create database connection pool with create if missing
-    let pool = SqlitePool::connect_with(
-        sqlx::sqlite::SqliteConnectOptions::new()
-            .filename(&db_path)
-            .create_if_missing(true)
-    )
-    .await
-    .expect("Failed to connect to database");
+    // An encrypted database waits for the frontend to call unlock_database with the passphrase
+    if api::settings::read_settings(&app_handle).database_encrypted {
+        println!("Database is encrypted, waiting for passphrase");
+        app_handle
+            .emit("database-locked", ())
+            .map_err(|e| format!("Failed to emit database lock: {}", e))?;
+        return Ok(());
+    }
     
-    // Run migrations
-    let migration_sql = include_str!("../migrations/1_create_tables.sql");
-    sqlx::query(migration_sql)
-        .execute(&pool)
+    // Connect (creating the file if missing) and run migrations
+    let pool = open_database(&db_path, None)
         .await
-        .expect("Failed to run migrations");
+        .map_err(|e| format!("Failed to open database {}: {}", db_path.display(), e))?;
     
     println!("Database initialized successfully");
     
-    // Manage the pool for use in commands
-    app_handle.manage(pool);
-}
\ No newline at end of file
+    // Manage the pool for use in commands; workspaces can swap it later
+    app_handle.manage(DbState::new(pool, None));
+    Ok(())
+}
+
+/**
+ * Emit a `reminder-due` event with the note id whenever a reminder comes due
+ */
+async fn poll_reminders(app_handle: tauri::AppHandle) {
+    // Remember what has fired so each reminder is only announced once per scheduled time
+    let mut announced: HashSet<(i64, i64)> = HashSet::new();
+    let mut interval = tokio::time::interval(Duration::from_secs(30));
+
+    loop {
+        interval.tick().await;
+
+        let Some(db) = app_handle.try_state::<DbState>() else {
+            continue;
+        };
+        let due = match api::notes::due_reminders(&db.pool()).await {
+            Ok(due) => due,
+            Err(e) => {
+                eprintln!("Failed to check reminders: {}", e);
+                continue;
+            }
+        };
+
+        announced.retain(|entry| due.contains(entry));
+        for entry in due {
+            if announced.contains(&entry) {
+                continue;
+            }
+            if let Err(e) = app_handle.emit("reminder-due", entry.0) {
+                eprintln!("Failed to emit reminder: {}", e);
+                continue;
+            }
+            announced.insert(entry);
+        }
+    }
+}
+
+/**
+ * Checkpoint the WAL and close the database pool before the process exits
+ */
+fn shutdown_database(app_handle: &tauri::AppHandle) {
+    // The pool is managed from an async task, so an early exit may happen before it exists
+    let Some(db) = app_handle.try_state::<DbState>() else {
+        return;
+    };
+    let pool = db.pool();
+
+    tauri::async_runtime::block_on(async move {
+        if let Err(e) = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(&pool)
+            .await
+        {
+            eprintln!("Failed to checkpoint database: {}", e);
+        }
+        pool.close().await;
+    });
+
+    println!("Database closed");
+}
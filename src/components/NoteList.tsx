@@ -57,9 +57,10 @@ export function NoteList(props: NoteListProps) {
     return "Empty note";
   };
 
-  const formatDate = (dateString?: string) => {
-    if (!dateString) return "";
-    const date = new Date(dateString);
+  // Timestamps arrive as Unix epoch milliseconds
+  const formatDate = (timestamp?: number) => {
+    if (!timestamp) return "";
+    const date = new Date(timestamp);
     return date.toLocaleDateString();
   };
 
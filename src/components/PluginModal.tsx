@@ -11,8 +11,19 @@ type PluginInfo = {
   description: string;
   path: string;
   enabled: boolean;
+  warnings: string[];
 };
 
+type BrokenPlugin = {
+  id: string;
+  path: string;
+  error: string;
+};
+
+type PluginListing =
+  | ({ status: "ok" } & PluginInfo)
+  | ({ status: "error" } & BrokenPlugin);
+
 interface PluginModalProps {
   isOpen: boolean;
   onClose: () => void;
@@ -20,6 +31,7 @@ interface PluginModalProps {
 
 export function PluginModal(props: PluginModalProps) {
   const [plugins, setPlugins] = createSignal<PluginInfo[]>([]);
+  const [brokenPlugins, setBrokenPlugins] = createSignal<BrokenPlugin[]>([]);
   const [isLoading, setIsLoading] = createSignal(false);
   const [error, setError] = createSignal<string | null>(null);
 
@@ -27,8 +39,9 @@ export function PluginModal(props: PluginModalProps) {
     try {
       setIsLoading(true);
       setError(null);
-      const list = await invoke<PluginInfo[]>("list_plugins");
-      setPlugins(list);
+      const list = await invoke<PluginListing[]>("list_plugins");
+      setPlugins(list.filter((p): p is { status: "ok" } & PluginInfo => p.status === "ok"));
+      setBrokenPlugins(list.filter((p): p is { status: "error" } & BrokenPlugin => p.status === "error"));
     } catch (e: any) {
       console.error("Failed to load plugins:", e);
       setError(String(e));
@@ -111,6 +124,9 @@ export function PluginModal(props: PluginModalProps) {
                       </div>
                       <div class="plugin-desc">{pl.description}</div>
                       <div class="plugin-meta">Path: {pl.path}</div>
+                      <For each={pl.warnings}>
+                        {(warning) => <div class="plugin-meta">Warning: {warning}</div>}
+                      </For>
                       <div class="plugin-actions-row">
                         <label class="toggle">
                           <input type="checkbox" checked={pl.enabled} onChange={(e) => togglePlugin(pl.id, e.currentTarget.checked)} />
@@ -122,6 +138,21 @@ export function PluginModal(props: PluginModalProps) {
                 </For>
               </div>
             </Show>
+            <Show when={brokenPlugins().length > 0}>
+              <div class="plugins-list">
+                <For each={brokenPlugins()}>
+                  {(pl) => (
+                    <div class="plugin-card">
+                      <div class="plugin-card-header">
+                        <div class="plugin-title">{pl.id}</div>
+                      </div>
+                      <div class="error">{pl.error}</div>
+                      <div class="plugin-meta">Path: {pl.path}</div>
+                    </div>
+                  )}
+                </For>
+              </div>
+            </Show>
           </Show>
         </div>
         
@@ -18,6 +18,7 @@ export interface UserTheme {
   display_name: string;
   content: string;
   file_path: string;
+  shadows_default: boolean;
 }
 
 // Built-in theme definitions (currently empty - all themes are user-defined)
@@ -133,7 +134,7 @@ class ThemeManager {
       // Apply user theme content
       this.themeElement.textContent = userTheme.content;
       this.currentTheme = themeName;
-      localStorage.setItem('lychee-theme', themeName);
+      this.persistTheme(themeName);
 
       // Dispatch theme change event
       window.dispatchEvent(new CustomEvent('theme-changed', { 
@@ -170,7 +171,7 @@ class ThemeManager {
     document.documentElement.offsetHeight;
     
     this.currentTheme = themeName;
-    localStorage.setItem('lychee-theme', themeName);
+    this.persistTheme(themeName);
     
 
     
@@ -182,6 +183,17 @@ class ThemeManager {
 
   }
 
+  /**
+   * Remembers the theme locally and in the backend settings, which toggle_theme_variant
+   * and the static site export read
+   */
+  private persistTheme(themeName: string) {
+    localStorage.setItem('lychee-theme', themeName);
+    invoke('set_active_theme', { themeName }).catch(error => {
+      console.error('Failed to save active theme:', error);
+    });
+  }
+
   async refreshUserThemes() {
     await this.loadAllThemes();
     // Re-populate theme selector
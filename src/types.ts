@@ -1,30 +1,39 @@
 export interface Note {
   id: number;
+  title: string;
   content: string;
-  created_at?: string;
-  updated_at?: string;
+  created_at: number;
+  updated_at: number;
+  is_pinned: boolean;
+  is_favorite: boolean;
 }
 
 export interface Tag {
   id: number;
   name: string;
+  color: string | null;
 }
 
 export interface NoteWithTags {
   id: number;
+  title: string;
   content: string;
-  created_at?: string;
-  updated_at?: string;
+  created_at: number;
+  updated_at: number;
+  is_pinned: boolean;
+  is_favorite: boolean;
   tags: string[];
 }
 
 export interface CreateNoteRequest {
+  title?: string | null;
   content: string;
   tags: string[];
 }
 
 export interface UpdateNoteRequest {
   id: number;
+  title?: string | null;
   content: string;
   tags: string[];
 }
\ No newline at end of file